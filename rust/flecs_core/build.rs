@@ -1,5 +1,3 @@
-use std::path::{PathBuf, Path};
-
 fn main() {
     // Tell cargo to invalidate the built crate whenever the sources change
     println!("cargo:rerun-if-changed=build.rs");
@@ -10,22 +8,31 @@ fn main() {
     if target.contains("emscripten") {
         // Get rid of the warning about unused command line arguments from emcc
         std::env::set_var("CFLAGS", "-Wno-unused-command-line-argument");
+        // Entity ids are 64-bit and carry generation and pair bits, so they have
+        // to cross the boundary as JS BigInts instead of being split or truncated
+        println!("cargo:rustc-link-arg=-sWASM_BIGINT");
     };
 
-    // // Bindgen
-    // let bindings = bindgen::Builder::default()
-    //     .header(Path::new("flecs.h").to_str().unwrap())
-    //     .generate()
-    //     .expect("Unable to generate bindings");
-
-    // let out_path = PathBuf::from("./src");
-    // bindings
-    //     .write_to_file(out_path.join("bindings.rs"))
-    //     .expect("Couldn't write bindings!");
+    // Bindings are checked in, so that building doesn't need libclang.
+    // FLECS_BINDGEN=1 regenerates src/bindings.rs from flecs.h.
+    println!("cargo:rerun-if-env-changed=FLECS_BINDGEN");
+    if std::env::var_os("FLECS_BINDGEN").is_some() {
+        bindgen::Builder::default()
+            .header("flecs.h")
+            // The CRT's mbstate_t isn't used by flecs, and on Windows its
+            // layout test only holds where c_ulong is 32-bit
+            .blocklist_type("_Mbstatet|mbstate_t")
+            .generate()
+            .expect("Unable to generate bindings")
+            .write_to_file("src/bindings.rs")
+            .expect("Couldn't write bindings!");
+    }
 
-    // Compile Flecs
+    // Compile Flecs. Soft asserts make invalid parameters an error that the
+    // failing call returns from, instead of aborting the host or wasm instance.
     cc::Build::new()
         .include("flecs.h")
+        .define("FLECS_SOFT_ASSERT", None)
         .file("flecs.c")
         .compile("flecs_core");
 }
@@ -0,0 +1,787 @@
+// C interface to the flecs_core exports. Every function here is a
+// #[no_mangle] extern "C" export of the flecs_core crate, which is built as
+// a static and a dynamic library. Keep this file in sync with the exports.
+
+#ifndef FLECS_CORE_H
+#define FLECS_CORE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include "flecs.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum flecs_type_t {
+    FlecsTypeU8 = 0,
+    FlecsTypeU16 = 1,
+    FlecsTypeU32 = 2,
+    FlecsTypeU64 = 3,
+    FlecsTypeI8 = 4,
+    FlecsTypeI16 = 5,
+    FlecsTypeI32 = 6,
+    FlecsTypeI64 = 7,
+    FlecsTypeF32 = 8,
+    FlecsTypeF64 = 9,
+    FlecsTypeBool = 10,
+    FlecsTypeString = 11,
+    FlecsTypeArray = 12,
+    FlecsTypeU32Array = 13,
+    FlecsTypeF32Array = 14,
+    FlecsTypeU8Vector = 15,
+    FlecsTypeU16Vector = 16,
+    FlecsTypeU32Vector = 17,
+    FlecsTypeU64Vector = 18,
+    FlecsTypeI8Vector = 19,
+    FlecsTypeI16Vector = 20,
+    FlecsTypeI32Vector = 21,
+    FlecsTypeI64Vector = 22,
+    FlecsTypeF32Vector = 23,
+    FlecsTypeF64Vector = 24,
+    FlecsTypeBoolVector = 25,
+    FlecsTypeStringVector = 26,
+} flecs_type_t;
+
+typedef enum flecs_relationship_trait_t {
+    FlecsRelationshipTraitExclusive = 0,
+    FlecsRelationshipTraitSymmetric = 1,
+    FlecsRelationshipTraitTransitive = 2,
+    FlecsRelationshipTraitReflexive = 3,
+    FlecsRelationshipTraitAcyclic = 4,
+    FlecsRelationshipTraitUnion = 5,
+    FlecsRelationshipTraitOneOf = 6,
+    FlecsRelationshipTraitTag = 7,
+} flecs_relationship_trait_t;
+
+typedef enum flecs_cleanup_condition_t {
+    FlecsCleanupConditionOnDelete = 0,
+    FlecsCleanupConditionOnDeleteTarget = 1,
+} flecs_cleanup_condition_t;
+
+typedef enum flecs_cleanup_action_t {
+    FlecsCleanupActionRemove = 0,
+    FlecsCleanupActionDelete = 1,
+    FlecsCleanupActionPanic = 2,
+} flecs_cleanup_action_t;
+
+typedef enum flecs_metric_kind_t {
+    FlecsMetricKindGauge = 0,
+    FlecsMetricKindCounter = 1,
+    FlecsMetricKindCounterIncrement = 2,
+    FlecsMetricKindCounterId = 3,
+} flecs_metric_kind_t;
+
+typedef enum flecs_alert_severity_t {
+    FlecsAlertSeverityInfo = 0,
+    FlecsAlertSeverityWarning = 1,
+    FlecsAlertSeverityError = 2,
+    FlecsAlertSeverityCritical = 3,
+} flecs_alert_severity_t;
+
+typedef enum flecs_http_method_t {
+    FlecsHttpMethodGet = 0,
+    FlecsHttpMethodPost = 1,
+    FlecsHttpMethodPut = 2,
+    FlecsHttpMethodDelete = 3,
+} flecs_http_method_t;
+
+typedef enum flecs_event_t {
+    FlecsEventOnAdd = 0,
+    FlecsEventOnRemove = 1,
+    FlecsEventOnSet = 2,
+    FlecsEventUnSet = 3,
+} flecs_event_t;
+
+typedef struct flecs_stats_t flecs_stats_t;
+
+typedef void (*flecs_xtor_callback_t)(void *ptr, int32_t count, int32_t size, void *ctx);
+typedef void (*flecs_copy_callback_t)(void *dst_ptr, const void *src_ptr, int32_t count, int32_t size, void *ctx);
+typedef void (*flecs_move_callback_t)(void *dst_ptr, void *src_ptr, int32_t count, int32_t size, void *ctx);
+typedef bool (*flecs_http_callback_t)(const ecs_http_request_t *request, ecs_http_reply_t *reply, void *ctx);
+typedef bool (*flecs_http_dispatcher_t)(uint32_t callback_index, const ecs_http_request_t *request, ecs_http_reply_t *reply, void *ctx);
+typedef void (*flecs_log_callback_t)(int32_t level, const char *file, int32_t line, const char *msg, void *ctx);
+typedef void (*flecs_log_dispatcher_t)(uint32_t callback_index, int32_t level, const char *file, int32_t line, const char *msg, void *ctx);
+typedef bool (*flecs_abort_callback_t)(int32_t code, const char *message);
+typedef void (*flecs_iter_callback_t)(ecs_iter_t *iter, void *ctx);
+typedef void (*flecs_callback_dispatcher_t)(uint32_t callback_index, ecs_iter_t *iter, void *ctx);
+
+typedef struct flecs_column_t {
+    void *ptr;
+    size_t stride;
+    int32_t count;
+} flecs_column_t;
+
+typedef struct flecs_type_hooks_t {
+    flecs_xtor_callback_t ctor;
+    flecs_xtor_callback_t dtor;
+    flecs_copy_callback_t copy;
+    flecs_move_callback_t move_;
+    flecs_iter_callback_t on_add;
+    flecs_iter_callback_t on_set;
+    flecs_iter_callback_t on_remove;
+    void *ctx;
+} flecs_type_hooks_t;
+
+typedef struct flecs_world_stats_t {
+    int32_t entity_count;
+    int32_t not_alive_entity_count;
+    int32_t component_count;
+    int32_t tag_count;
+    int32_t pair_count;
+    int32_t table_count;
+    int32_t empty_table_count;
+    int32_t query_count;
+    int32_t observer_count;
+    int32_t system_count;
+    int32_t frame_count;
+    int32_t merge_count;
+    int32_t systems_ran;
+    int32_t observers_ran;
+    double frame_time;
+    double system_time;
+    double emit_time;
+    double merge_time;
+    double delta_time;
+    double world_time;
+    double fps;
+} flecs_world_stats_t;
+
+typedef struct flecs_system_stats_t {
+    ecs_entity_t system;
+    double time_spent;
+    int32_t invoke_count;
+    int32_t matched_entity_count;
+    int32_t matched_table_count;
+} flecs_system_stats_t;
+
+typedef struct flecs_metric_instance_t {
+    ecs_entity_t source;
+    double value;
+} flecs_metric_instance_t;
+
+typedef struct flecs_metric_value_t {
+    int32_t count;
+    double sum;
+    double min;
+    double max;
+    double avg;
+} flecs_metric_value_t;
+
+typedef struct flecs_alert_instance_t {
+    ecs_entity_t alert;
+    ecs_entity_t entity;
+    uint8_t severity;
+    double duration;
+    const char *message;
+} flecs_alert_instance_t;
+
+typedef struct flecs_allocator_t {
+    ecs_os_api_malloc_t malloc;
+    ecs_os_api_realloc_t realloc;
+    ecs_os_api_calloc_t calloc;
+    ecs_os_api_free_t free;
+} flecs_allocator_t;
+
+typedef struct flecs_memory_stats_t {
+    int64_t live_bytes;
+    int64_t live_count;
+    int64_t frame_alloc_count;
+    int64_t frame_alloc_bytes;
+    int64_t frame_free_count;
+} flecs_memory_stats_t;
+
+void flecs_init(void);
+
+ecs_entity_t flecs_component_create(const char *component_name, const char *const *member_names, uint32_t member_names_count, const uint8_t *member_types, uint32_t member_types_size);
+
+// Component without reflection data, for types whose layout is only known
+// to the caller
+ecs_entity_t flecs_component_create_sized(const char *component_name, int32_t size, int32_t alignment);
+
+ecs_entity_t flecs_tag_create(const char *tag_name);
+
+ecs_entity_t flecs_component_get(const char *name);
+
+ecs_entity_t flecs_entity_create(void);
+
+ecs_entity_t flecs_prefab_create(void);
+
+ecs_entity_t flecs_prefab_instance(ecs_entity_t prefab);
+
+ecs_entity_t flecs_entity_create_named(const char *name);
+
+const ecs_entity_t *flecs_entity_create_bulk(int32_t count);
+
+const ecs_entity_t *flecs_entity_create_bulk_components(int32_t entity_count, uint32_t component_count, const ecs_entity_t *components);
+
+// Spawns entity_count entities and initializes their components from column
+// buffers. data[i] points to entity_count contiguous values of components[i],
+// or is null for tags and components that should stay default constructed.
+// Returns a malloc'd copy of the new entity ids which the caller releases
+// with m_free, since the array returned by ecs_bulk_init is owned by flecs
+// and invalidated by the next entity creation.
+ecs_entity_t *flecs_entity_create_bulk_data(int32_t entity_count, uint32_t component_count, const ecs_entity_t *components, void *const *data);
+
+// Singletons live on their own component entity, like with ecs_singleton_*,
+// so query terms with the $ source match them
+void flecs_singleton_add(ecs_entity_t component);
+
+void flecs_singleton_set(ecs_entity_t component, const void *data);
+
+// Returns null if the singleton isn't set
+const void *flecs_singleton_get(ecs_entity_t component);
+
+// Adds the singleton if it isn't set. Call flecs_singleton_modified after
+// writing through the pointer to run OnSet observers.
+void *flecs_singleton_get_mut(ecs_entity_t component);
+
+bool flecs_singleton_has(ecs_entity_t component);
+
+void flecs_singleton_modified(ecs_entity_t component);
+
+void flecs_singleton_remove(ecs_entity_t component);
+
+// Returns null if the entity isn't alive or the id doesn't carry data
+void *flecs_entity_get_component(ecs_entity_t entity, ecs_entity_t component);
+
+// Runs OnSet observers after the component was written through
+// flecs_entity_get_component
+void flecs_entity_component_modified(ecs_entity_t entity, ecs_entity_t component);
+
+// Refs cache where an entity stores a component, so getting it again skips
+// the lookup and still works after the entity moved to another table.
+// Returns null if the entity isn't alive; release with flecs_ref_destroy.
+ecs_ref_t *flecs_ref_create(ecs_entity_t entity, ecs_entity_t component);
+
+// Returns null if the entity doesn't have the component or was deleted
+void *flecs_ref_get(ecs_ref_t *component_ref);
+
+// Resyncs the cached table record, so a later flecs_ref_get doesn't have to
+void flecs_ref_update(ecs_ref_t *component_ref);
+
+void flecs_ref_destroy(ecs_ref_t *component_ref);
+
+void flecs_entity_add_component(ecs_entity_t entity, ecs_entity_t component);
+
+void flecs_entity_remove_component(ecs_entity_t entity, ecs_entity_t component);
+
+void flecs_entity_add_tag(ecs_entity_t entity, ecs_entity_t tag);
+
+void flecs_entity_child_of(ecs_entity_t entity, ecs_entity_t parent);
+
+ecs_iter_t *flecs_entity_children(ecs_entity_t parent);
+
+bool flecs_term_next(ecs_iter_t *iter);
+
+ecs_entity_t *flecs_child_entities(ecs_iter_t *iter);
+
+void flecs_entity_add_pair(ecs_entity_t entity, ecs_entity_t relationship, ecs_entity_t target);
+
+void flecs_entity_remove_pair(ecs_entity_t entity, ecs_entity_t relationship, ecs_entity_t target);
+
+bool flecs_entity_has_pair(ecs_entity_t entity, ecs_entity_t relationship, ecs_entity_t target);
+
+// Component that holds the data of a pair. This is the relationship unless it
+// has the Tag trait, in which case the target carries the data.
+// Returns 0 if neither element carries data.
+ecs_entity_t flecs_pair_typeid(ecs_entity_t relationship, ecs_entity_t target);
+
+// Pointer to the pair data, which can be read and written with the same
+// flecs_component_get_member_* / flecs_component_set_member_* accessors.
+// Returns null if the entity isn't alive or the pair doesn't carry data.
+void *flecs_entity_get_pair(ecs_entity_t entity, ecs_entity_t relationship, ecs_entity_t target);
+
+// Copies data into the pair, adding it if the entity doesn't have it yet.
+// Returns false without adding the pair if the entity isn't alive or the pair
+// doesn't carry data, in which case flecs_entity_add_pair is the one to use.
+bool flecs_entity_set_pair(ecs_entity_t entity, ecs_entity_t relationship, ecs_entity_t target, const void *data);
+
+// Pair data for the index-th target of a relationship, which is how the
+// values of a (Likes, *) pair are walked. Returns null past the last target,
+// and records an error if the pair doesn't carry data.
+void *flecs_entity_get_pair_at(ecs_entity_t entity, ecs_entity_t relationship, int32_t index);
+
+void flecs_entity_pair_modified(ecs_entity_t entity, ecs_entity_t relationship, ecs_entity_t target);
+
+// Returns 0 once index is past the last target, so guests can loop until 0
+ecs_entity_t flecs_entity_get_target(ecs_entity_t entity, ecs_entity_t relationship, int32_t index);
+
+ecs_entity_t flecs_entity_get_parent(ecs_entity_t entity);
+
+// Collects all targets of a relationship into a malloc'd array that the
+// caller owns and releases with m_free. The length is written to count.
+ecs_entity_t *flecs_entity_get_targets(ecs_entity_t entity, ecs_entity_t relationship, int32_t *count);
+
+// Returns false if the trait code is unknown
+bool flecs_relationship_add_trait(ecs_entity_t relationship, uint8_t relationship_trait);
+
+// Adds a cleanup policy such as (OnDeleteTarget, Delete) to a relationship or
+// component. Returns false if the condition or action code is unknown.
+bool flecs_entity_add_cleanup_policy(ecs_entity_t entity, uint8_t condition, uint8_t action);
+
+ecs_entity_t flecs_wildcard(void);
+
+ecs_entity_t flecs_any(void);
+
+bool flecs_is_pair(ecs_id_t id);
+
+ecs_entity_t flecs_pair_first(ecs_id_t pair);
+
+ecs_entity_t flecs_pair_second(ecs_id_t pair);
+
+// Resolves the id a term actually matched, which for a (R, *) term
+// carries the concrete target of the relationship
+ecs_entity_t flecs_iter_pair_target(ecs_iter_t *iter, int32_t term_index);
+
+ecs_query_desc_t *flecs_query_create(void);
+
+uint8_t flecs_query_with(ecs_query_desc_t *query_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+uint8_t flecs_query_without(ecs_query_desc_t *query_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+uint8_t flecs_query_with_or(ecs_query_desc_t *query_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+// Adds terms that match the singletons of the ids instead of the matched
+// entity, like Game($) in the query DSL. Returns the next free term index,
+// also when there are no ids.
+uint8_t flecs_query_with_singleton(ecs_query_desc_t *query_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+ecs_query_t *flecs_query_build(ecs_query_desc_t *desc);
+
+bool flecs_query_next(ecs_iter_t *iter);
+
+ecs_iter_t *flecs_query_iter(ecs_query_t *query);
+
+// Releases the resources of an iterator that is dropped before its next
+// function returned false
+void flecs_iter_fini(ecs_iter_t *iter);
+
+// Frees an iterator returned by flecs_query_iter, flecs_filter_iter or
+// flecs_entity_children
+void flecs_iter_destroy(ecs_iter_t *iter);
+
+void flecs_query_destroy(ecs_query_t *query);
+
+int32_t flecs_iter_count(ecs_iter_t *iter);
+
+// Advances a query iterator by one matched table without populating the
+// iterator fields. Columns of the table are then read directly through the
+// flecs_table_* exports. Call flecs_query_populate if the entity ids or
+// field pointers of the current table are also needed.
+// Change detection only sees writes to the columns of tables that were
+// populated, so call flecs_query_populate before writing to a table if other
+// queries check for changes.
+bool flecs_query_next_table(ecs_iter_t *iter);
+
+int32_t flecs_query_populate(ecs_iter_t *iter, bool when_changed);
+
+ecs_table_t *flecs_iter_table(ecs_iter_t *iter);
+
+int32_t flecs_table_count(ecs_table_t *table);
+
+int32_t flecs_table_column_count(ecs_table_t *table);
+
+// Index of the column storing the component, or -1 for tags and ids the
+// table doesn't have
+int32_t flecs_table_get_column_index(ecs_table_t *table, ecs_id_t id);
+
+void *flecs_table_get_column(ecs_table_t *table, int32_t column_index, int32_t offset);
+
+size_t flecs_table_get_column_size(ecs_table_t *table, int32_t column_index);
+
+// Fills one column view per id for the table, so that a guest can map every
+// field of the current table as a typed array with a single call. Ids
+// without a column in the table (tags, or components the table doesn't
+// have) produce a null pointer with a stride of 0. Returns the row count.
+int32_t flecs_table_columns(ecs_table_t *table, const ecs_id_t *ids, int32_t ids_count, flecs_column_t *columns);
+
+void **flecs_iter_ptrs(ecs_iter_t *iter, uint32_t component_query_index);
+
+const uint8_t *flecs_query_iter_component(uint8_t *component_array_ptr, uint32_t component_index, uint32_t count, ecs_entity_t component_id);
+
+// False when the field is shared, i.e. matched on another entity such as a
+// prefab. A shared field has one value for all entities of the iterated table.
+bool flecs_field_is_self(ecs_iter_t *iter, int32_t term_index);
+
+// False when an optional field didn't match, in which case it has no data
+bool flecs_field_is_set(ecs_iter_t *iter, int32_t term_index);
+
+// Entity the field was matched on, or 0 when matched on the iterated entities
+ecs_entity_t flecs_field_src(ecs_iter_t *iter, int32_t term_index);
+
+// Id the field was matched with, which resolves wildcards to the actual pair
+ecs_id_t flecs_field_id(ecs_iter_t *iter, int32_t term_index);
+
+const void *flecs_query_field(ecs_iter_t *iter, int32_t term_index, uint32_t count, uint32_t index);
+
+size_t flecs_query_field_size(ecs_iter_t *iter, int32_t term_index);
+
+const void **flecs_query_field_list(ecs_iter_t *iter, int32_t term_index, uint32_t count);
+
+ecs_entity_t flecs_query_entity(ecs_iter_t *iter, uint32_t count, uint32_t index);
+
+ecs_entity_t *flecs_query_entity_list(ecs_iter_t *iter);
+
+ecs_entity_t *flecs_query_entity_list(ecs_iter_t *iter);
+
+ecs_filter_desc_t *flecs_filter_create(void);
+
+uint8_t flecs_filter_with(ecs_filter_desc_t *filter_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+uint8_t flecs_filter_without(ecs_filter_desc_t *filter_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+uint8_t flecs_filter_with_or(ecs_filter_desc_t *filter_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+// Adds terms that match the singletons of the ids instead of the matched
+// entity, like Game($) in the query DSL. Returns the next free term index,
+// also when there are no ids.
+uint8_t flecs_filter_with_singleton(ecs_filter_desc_t *filter_desc, uint8_t filter_index, ecs_entity_t *ids, int32_t components_count);
+
+ecs_filter_t *flecs_filter_build(ecs_filter_desc_t *desc);
+
+bool flecs_filter_next(ecs_iter_t *iter);
+
+const uint8_t *flecs_filter_iter_component(uint8_t *component_array_ptr, uint32_t component_index, uint32_t count, ecs_entity_t component_id);
+
+const void *flecs_filter_field(ecs_iter_t *iter, int32_t term_index, uint32_t count, uint32_t index);
+
+size_t flecs_filter_field_size(ecs_iter_t *iter, int32_t term_index);
+
+const void **flecs_filter_field_list(ecs_iter_t *iter, int32_t term_index, uint32_t count);
+
+ecs_entity_t flecs_filter_entity(ecs_iter_t *iter, uint32_t count, uint32_t index);
+
+ecs_entity_t *flecs_filter_entity_list(ecs_iter_t *iter);
+
+void flecs_component_set_member_u8(void *component_ptr, uint32_t offset, uint8_t value);
+
+uint8_t flecs_component_get_member_u8(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_u16(void *component_ptr, uint32_t offset, uint16_t value);
+
+uint16_t flecs_component_get_member_u16(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_u32(void *component_ptr, uint32_t offset, uint32_t value);
+
+uint32_t flecs_component_get_member_u32(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_u64(void *component_ptr, uint32_t offset, uint64_t value);
+
+uint64_t flecs_component_get_member_u64(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_i8(void *component_ptr, uint32_t offset, int8_t value);
+
+int8_t flecs_component_get_member_i8(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_i16(void *component_ptr, uint32_t offset, int16_t value);
+
+int16_t flecs_component_get_member_i16(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_i32(void *component_ptr, uint32_t offset, int32_t value);
+
+int32_t flecs_component_get_member_i32(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_i64(void *component_ptr, uint32_t offset, int64_t value);
+
+int64_t flecs_component_get_member_i64(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_f32(void *component_ptr, uint32_t offset, float value);
+
+float flecs_component_get_member_f32(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_f64(void *component_ptr, uint32_t offset, double value);
+
+double flecs_component_get_member_f64(void *component_ptr, uint32_t offset);
+
+void flecs_component_set_member_bool(void *component_ptr, uint32_t offset, bool value);
+
+bool flecs_component_get_member_bool(void *component_ptr, uint32_t offset);
+
+// String, array and vector members are owned by the component: the setters
+// copy the value, so callers keep ownership of theirs, and the default hooks
+// free, copy and move them with the component. The setters return false when
+// the component has no member of their kind at offset, since they free the
+// value that is there.
+bool flecs_component_set_member_string(ecs_entity_t component, void *component_ptr, uint32_t offset, const char *value);
+
+char *flecs_component_get_member_string(void *component_ptr, uint32_t offset);
+
+bool flecs_component_set_member_u32array(ecs_entity_t component, void *component_ptr, uint32_t offset, const uint32_t *value);
+
+uint32_t *flecs_component_get_member_u32array(void *component_ptr, uint32_t offset);
+
+bool flecs_component_set_member_f32array(ecs_entity_t component, void *component_ptr, uint32_t offset, const float *value);
+
+float *flecs_component_get_member_f32array(void *component_ptr, uint32_t offset);
+
+// Replaces the elements of a vector member with a copy of count values of
+// element_size bytes. Returns false when the component has no vector member
+// at offset, or its elements aren't element_size bytes.
+bool flecs_component_set_member_vector(ecs_entity_t component, void *component_ptr, uint32_t offset, const void *values, int32_t count, int32_t element_size);
+
+// Replaces the elements of a string vector member with copies of the strings.
+// Returns false when the component has no string vector member at offset.
+bool flecs_component_set_member_string_vector(ecs_entity_t component, void *component_ptr, uint32_t offset, const char *const *values, int32_t count);
+
+// Returns the elements of a vector member and writes their count
+void *flecs_component_get_member_vector(void *component_ptr, uint32_t offset, int32_t *count);
+
+// Sets lifecycle hooks of a component, before it's added to any entity.
+// Callbacks left null keep their current behavior. A ctor, dtor, copy or move
+// replaces the default that manages string, array and vector members, so it
+// has to free and copy those itself. Returns false when the component is in use.
+bool flecs_component_set_hooks(ecs_entity_t component, const flecs_type_hooks_t *hooks);
+
+bool flecs_progress(float delta_time);
+
+// Records the next frame_count frames of flecs_progress and writes them to
+// path as a Chrome Trace Event file. Only systems created with
+// flecs_system_build that keep its run action are timed, and their phase is
+// the one the pipeline runs them in, found up their DependsOn chain. Frames
+// run with ecs_progress directly aren't recorded. Returns false if a
+// recording is already running or path is null. If the file can't be written
+// after the last frame, the error is logged and set as the last error of the
+// thread that ran it.
+bool flecs_trace_start(int32_t frame_count, const char *path);
+
+// Ends a recording before its frames ran out and writes it. Returns false if
+// there was no recording or the file couldn't be written.
+bool flecs_trace_stop(void);
+
+// Measures frame and system time from here on, which the stats need
+flecs_stats_t *flecs_stats_create(void);
+
+void flecs_stats_update(flecs_stats_t *stats);
+
+void flecs_stats_world(const flecs_stats_t *stats, flecs_world_stats_t *world_stats);
+
+// Returns a malloc'd array of the systems the pipeline ran, which the caller
+// releases with m_free, and writes their count. Null when there are none.
+flecs_system_stats_t *flecs_stats_systems(const flecs_stats_t *stats, int32_t *count);
+
+// The world and system stats of the last sample as JSON. Returns a malloc'd string the caller releases with m_free.
+char *flecs_stats_to_json(const flecs_stats_t *stats);
+
+void flecs_stats_destroy(flecs_stats_t *stats);
+
+// Metric on a member of a component, e.g. "health" or "stats.health" of
+// Enemy, measured on every entity with the component. Metrics update during
+// flecs_progress. Returns 0 if the member doesn't exist or can't be measured,
+// or if the kind is unknown.
+ecs_entity_t flecs_metric_create_member(const char *name, ecs_entity_t component, const char *member, uint8_t kind);
+
+// Metric on whether entities have an id. A gauge is 1 for each entity with
+// the id, CounterId accumulates the number of entities times the frame time.
+// With targets, an (R, *) pair gets a value per target. Returns 0 if the
+// metric can't be created or the kind is unknown.
+ecs_entity_t flecs_metric_create_id(const char *name, ecs_id_t id, uint8_t kind, bool targets);
+
+// Returns false if the metric has no instances
+bool flecs_metric_value(ecs_entity_t metric, flecs_metric_value_t *value);
+
+// Returns a malloc'd array of the metric's instances, which the caller
+// releases with m_free, and writes their count. Null when there are none.
+flecs_metric_instance_t *flecs_metric_instances(ecs_entity_t metric, int32_t *count);
+
+// Alert raised for every entity matching a query expression such as
+// "Health, !Position". The message may refer to query variables, e.g.
+// "$this has Health but no Position". Alerts are evaluated every half second
+// of world time during flecs_progress, and an alert that stops matching is
+// cleared after retain_period seconds. Returns 0 if the query doesn't parse
+// or the severity is unknown.
+ecs_entity_t flecs_alert_create(const char *name, const char *expr, const char *message, uint8_t severity, float retain_period);
+
+// Number of active alerts for an entity, or whether one alert is active for
+// it if alert isn't 0
+int32_t flecs_entity_alert_count(ecs_entity_t entity, ecs_entity_t alert);
+
+// Returns a malloc'd array of the active instances of an alert, or of all
+// alerts if alert is 0, which the caller releases with m_free, and writes
+// their count. Null when there are none.
+flecs_alert_instance_t *flecs_alerts_active(ecs_entity_t alert, int32_t *count);
+
+// Listens on a port, 27750 if 0. Requests are answered during flecs_progress.
+// Returns false if the port can't be opened or the server already listens.
+#ifndef __EMSCRIPTEN__
+bool flecs_rest_enable(uint16_t port);
+#endif
+
+void flecs_rest_disable(void);
+
+// Answers a REST request such as ("GET", "/entity/flecs/core/World") without
+// a socket. Writes the HTTP status code and returns the body, which the caller
+// releases with m_free, or null if the reply has no body.
+char *flecs_rest_request(const char *method, const char *path, int32_t *code);
+
+// Registers a route, replacing the handler of an existing route with the same
+// method and path. Query parameters aren't part of the path.
+void flecs_http_route(uint8_t method, const char *path, flecs_http_callback_t callback, void *ctx);
+
+void flecs_http_route_indexed(uint8_t method, const char *path, uint32_t callback_index, void *ctx);
+
+void flecs_http_route_remove(uint8_t method, const char *path);
+
+// Registers the host function that runs handlers created with
+// flecs_http_route_indexed
+void flecs_set_http_dispatcher(flecs_http_dispatcher_t dispatcher);
+
+// Listens for the custom routes on a port. Requests are answered during
+// flecs_progress. Returns false if the port can't be opened or the server
+// already listens.
+#ifndef __EMSCRIPTEN__
+bool flecs_http_enable(uint16_t port);
+#endif
+
+void flecs_http_disable(void);
+
+// Answers a request for a custom route, like flecs_rest_request
+char *flecs_http_request(const char *method, const char *path, int32_t *code);
+
+uint8_t flecs_http_request_method(const ecs_http_request_t *request);
+
+const char *flecs_http_request_path(const ecs_http_request_t *request);
+
+const char *flecs_http_request_body(const ecs_http_request_t *request);
+
+// Decoded value of a query parameter, or null if the request doesn't have it
+const char *flecs_http_request_param(const ecs_http_request_t *request, const char *name);
+
+const char *flecs_http_request_header(const ecs_http_request_t *request, const char *name);
+
+// Sets the status code of a reply and appends to its JSON body. The body is
+// copied.
+void flecs_http_reply_set(ecs_http_reply_t *reply, int32_t code, const char *body);
+
+// Messages above the level aren't logged. Returns the previous level.
+int32_t flecs_log_set_level(int32_t level);
+
+int32_t flecs_log_get_level(void);
+
+// Returns the previous setting
+bool flecs_log_enable_colors(bool enabled);
+
+// Returns the previous setting
+bool flecs_log_enable_timestamp(bool enabled);
+
+// Sends log messages to a callback instead of the console, or back to the
+// console if the callback is null
+void flecs_log_set_callback(flecs_log_callback_t callback, void *ctx);
+
+void flecs_log_set_callback_indexed(uint32_t callback_index, void *ctx);
+
+// Registers the host function that runs callbacks set with
+// flecs_log_set_callback_indexed
+void flecs_set_log_dispatcher(flecs_log_dispatcher_t dispatcher);
+
+// Sends log messages to the logger of the log crate, with "flecs" as target
+void flecs_log_to_rust(void);
+
+// Sets the hook that is called when flecs would abort, or none if hook is
+// null. flecs can't continue the call that hit the error, so the hook chooses
+// how it ends: returning false aborts the process or wasm instance, returning
+// true replaces the abort. On wasm the instance then traps, which the host can
+// catch as an exception and keep the instance for, e.g. to read the last error
+// or to save state. Natively the process exits with the error code, without
+// the abort signal or a core dump.
+void flecs_set_abort_hook(flecs_abort_callback_t hook);
+
+// Code of the last error on this thread, such as ECS_INVALID_PARAMETER, or 0
+// if there was none since flecs_clear_error. Errors flecs logs without a code
+// are ECS_OPERATION_FAILED.
+int32_t flecs_last_error(void);
+
+// Message of the last error on this thread, or null. Valid until the next
+// error or flecs_clear_error.
+const char *flecs_last_error_message(void);
+
+void flecs_clear_error(void);
+
+// Installs the allocator flecs uses, or the default if allocator is null.
+// Returns false if the world already exists or a function is missing.
+bool flecs_set_allocator(const flecs_allocator_t *allocator);
+
+// Tracks the memory flecs allocates, on top of the installed allocator.
+// Returns false if the world already exists.
+bool flecs_set_memory_tracking(bool enabled);
+
+// Frames end with flecs_progress. Returns false if memory isn't tracked.
+bool flecs_memory_stats(flecs_memory_stats_t *stats);
+
+ecs_entity_t flecs_make_pair(ecs_entity_t relation, ecs_entity_t object);
+
+ecs_filter_t *flecs_filter_children_init(ecs_entity_t id);
+
+ecs_iter_t *flecs_filter_iter(ecs_filter_t *filter);
+
+const ecs_entity_t *flecs_iter_entities(ecs_iter_t *iter, int32_t *count);
+
+void flecs_delete_entity(ecs_entity_t entity);
+
+// Deletes every entity with the id, which may be a pair or wildcard.
+// Unloading a level is flecs_delete_with(flecs_make_pair(EcsChildOf, level)).
+void flecs_delete_with(ecs_id_t id);
+
+// Removes the id from every entity that has it, without deleting the entities
+void flecs_remove_all(ecs_id_t id);
+
+// Compacts memory after large unloads. Tables that stayed empty for more than
+// clear_generation calls release their storage, and tables empty for more than
+// delete_generation calls are deleted. A non-zero id limits the cleanup to
+// tables with that id. Returns the number of deleted tables.
+int32_t flecs_delete_empty_tables(ecs_id_t id, uint16_t clear_generation, uint16_t delete_generation, int32_t min_id_count, double time_budget_seconds);
+
+bool flecs_entity_has_component(ecs_entity_t entity, ecs_entity_t component);
+
+bool flecs_is_valid(ecs_entity_t entity);
+
+void m_free(void *ptr);
+
+void flecs_component_set_member_ptr(void *component_ptr, uint32_t offset, void *value);
+
+void *flecs_component_get_member_ptr(void *component_ptr, uint32_t offset);
+
+// Registers the host function that runs callbacks created with
+// flecs_system_create_indexed and flecs_observer_create_indexed
+void flecs_set_callback_dispatcher(flecs_callback_dispatcher_t dispatcher);
+
+ecs_system_desc_t *flecs_system_create(flecs_iter_callback_t callback, void *ctx);
+
+ecs_system_desc_t *flecs_system_create_indexed(uint32_t callback_index, void *ctx);
+
+// System that calls a plain flecs iter action, without a context
+ecs_system_desc_t *flecs_system_create_hook(ecs_iter_action_t hook);
+
+ecs_entity_t flecs_system_build(ecs_system_desc_t *system_desc);
+
+ecs_query_desc_t *flecs_query_from_system_desc(ecs_system_desc_t *system_desc);
+
+ecs_observer_desc_t *flecs_observer_create(flecs_iter_callback_t callback, void *ctx);
+
+ecs_observer_desc_t *flecs_observer_create_indexed(uint32_t callback_index, void *ctx);
+
+// Observer that calls a plain flecs iter action, without a context
+ecs_observer_desc_t *flecs_observer_create_hook(ecs_iter_action_t hook);
+
+// Adds one of the Event kinds to the observer. Returns false when all event
+// slots are taken.
+bool flecs_observer_add_event(ecs_observer_desc_t *observer_desc, uint8_t event);
+
+ecs_filter_desc_t *flecs_filter_from_observer_desc(ecs_observer_desc_t *observer_desc);
+
+ecs_entity_t flecs_observer_build(ecs_observer_desc_t *observer_desc);
+
+ecs_entity_t flecs_component_lookup(char *name);
+
+char *flecs_entity_to_json(ecs_entity_t entity);
+
+void flecs_json_to_entity(char *json);
+
+void flecs_entity_set_name(ecs_entity_t entity, char *name);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
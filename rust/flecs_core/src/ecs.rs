@@ -0,0 +1,815 @@
+// Safe Rust layer over the flecs_* exports. References into component storage
+// borrow the World, and everything that can move an entity to another table
+// needs the World mutably, so the borrow checker rules out pointers that
+// flecs would invalidate.
+
+// Unsafe functions of the safe layer document their contract
+#![warn(clippy::missing_safety_doc)]
+
+use crate::*;
+use once_cell::sync::Lazy;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+pub use flecs_core_derive::Component;
+
+// Set while a World handle exists
+static WORLD_TAKEN: AtomicBool = AtomicBool::new(false);
+
+// Ids of the Rust types registered as components
+static COMPONENT_IDS: Lazy<Mutex<HashMap<TypeId, ecs_entity_t>>> = Lazy::new(Default::default);
+
+// A Rust type stored in flecs. Components are moved around with memcpy and
+// constructed with Default, so they have to be Copy + Default.
+pub trait Component: Copy + Default + 'static {
+    // Path the type is registered under, e.g.
+    // concat!(module_path!(), "::Position"), which #[derive(Component)] sets.
+    // Taken from the source instead of type_name, which isn't stable across
+    // compiler versions.
+    const NAME: &'static str;
+
+    // Registers the type and returns its id. Only gives flecs the size,
+    // alignment and constructor unless overridden, e.g. by #[derive(Component)]
+    fn register() -> ecs_entity_t {
+        register_component::<Self>()
+    }
+}
+
+// Name a component is registered under. Module paths become flecs scopes.
+pub fn component_name<T: Component>() -> String {
+    T::NAME.replace("::", ".")
+}
+
+// Creates the component entity for T with its size, alignment and a
+// constructor that writes T::default(). Zero sized types become tags.
+pub fn register_component<T: Component>() -> ecs_entity_t {
+    let name = CString::new(component_name::<T>()).unwrap();
+    unsafe {
+        if std::mem::size_of::<T>() == 0 {
+            return flecs_tag_create(name.as_ptr());
+        }
+        let component = flecs_component_create_sized(
+            name.as_ptr(),
+            std::mem::size_of::<T>() as i32,
+            std::mem::align_of::<T>() as i32,
+        );
+        let mut hooks: ecs_type_hooks_t = std::mem::MaybeUninit::zeroed().assume_init();
+        hooks.ctor = Some(default_ctor::<T>);
+        ecs_set_hooks_id(*WORLD, component, &hooks);
+        component
+    }
+}
+
+unsafe extern "C" fn default_ctor<T: Component>(ptr: *mut c_void, count: i32, _type_info: *const ecs_type_info_t) {
+    for index in 0..count as usize {
+        (ptr as *mut T).add(index).write(T::default());
+    }
+}
+
+// Member of a struct component, as emitted by #[derive(Component)]
+pub struct MemberDesc {
+    pub name: &'static str,
+    pub type_: ecs_entity_t,
+    // Element count for inline arrays, 0 otherwise
+    pub count: i32,
+    // Offset as laid out by Rust, checked against what flecs computes
+    pub offset: usize,
+}
+
+// Registers T with reflection data for its members. Panics when flecs lays
+// out the members differently than Rust did.
+pub fn register_struct<T: Component>(members: &[MemberDesc]) -> ecs_entity_t {
+    let component = register_component::<T>();
+    let names: Vec<CString> = members.iter().map(|member| CString::new(member.name).unwrap()).collect();
+    unsafe {
+        let mut struct_desc: ecs_struct_desc_t = std::mem::MaybeUninit::zeroed().assume_init();
+        struct_desc.entity = component;
+        for (index, member) in members.iter().enumerate() {
+            struct_desc.members[index].name = names[index].as_ptr();
+            struct_desc.members[index].type_ = member.type_;
+            struct_desc.members[index].count = member.count;
+        }
+        if ecs_struct_init(*WORLD, &struct_desc) == 0 {
+            panic!("flecs rejected the members of {}", component_name::<T>());
+        }
+
+        let reflected = ecs_get_id(*WORLD, component, FLECS_IDEcsStructID_) as *const EcsStruct;
+        let mut index = 0;
+        ecs_vector_each::<ecs_member_t, _>(&(*reflected).members, |reflected| {
+            let member = &members[index];
+            assert_eq!(
+                reflected.offset as usize, member.offset,
+                "flecs puts {}.{} at a different offset than Rust",
+                component_name::<T>(), member.name,
+            );
+            index += 1;
+        });
+        let size = (*(ecs_get_id(*WORLD, component, FLECS_IDEcsComponentID_) as *const EcsComponent)).size;
+        assert_eq!(size as usize, std::mem::size_of::<T>(), "flecs has a different size for {}", component_name::<T>());
+    }
+    component
+}
+
+// Registers a fieldless enum with its constants
+pub fn register_enum<T: Component>(constants: &[(&str, i32)]) -> ecs_entity_t {
+    let component = register_component::<T>();
+    let names: Vec<CString> = constants.iter().map(|(name, _)| CString::new(*name).unwrap()).collect();
+    unsafe {
+        let mut enum_desc: ecs_enum_desc_t = std::mem::MaybeUninit::zeroed().assume_init();
+        enum_desc.entity = component;
+        for (index, (_, value)) in constants.iter().enumerate() {
+            enum_desc.constants[index].name = names[index].as_ptr();
+            enum_desc.constants[index].value = *value;
+        }
+        if ecs_enum_init(*WORLD, &enum_desc) == 0 {
+            panic!("flecs rejected the constants of {}", component_name::<T>());
+        }
+    }
+    component
+}
+
+// Types that can be members of a struct component
+pub trait Member {
+    // Element count when the member is an inline array
+    const COUNT: i32 = 0;
+    fn member_type() -> ecs_entity_t;
+}
+
+macro_rules! impl_member {
+    ($($ty:ty => $id:ident),+ $(,)?) => {
+        $(impl Member for $ty {
+            fn member_type() -> ecs_entity_t {
+                unsafe { $id }
+            }
+        })+
+    };
+}
+
+impl_member!(
+    bool => FLECS_IDecs_bool_tID_,
+    u8 => FLECS_IDecs_u8_tID_,
+    u16 => FLECS_IDecs_u16_tID_,
+    u32 => FLECS_IDecs_u32_tID_,
+    u64 => FLECS_IDecs_u64_tID_,
+    i8 => FLECS_IDecs_i8_tID_,
+    i16 => FLECS_IDecs_i16_tID_,
+    i32 => FLECS_IDecs_i32_tID_,
+    i64 => FLECS_IDecs_i64_tID_,
+    f32 => FLECS_IDecs_f32_tID_,
+    f64 => FLECS_IDecs_f64_tID_,
+    usize => FLECS_IDecs_uptr_tID_,
+    isize => FLECS_IDecs_iptr_tID_,
+    Entity => FLECS_IDecs_entity_tID_,
+);
+
+// Arrays are inline members with a count. Arrays of arrays get an array type
+// for the inner array.
+impl<T: Member, const N: usize> Member for [T; N] {
+    const COUNT: i32 = N as i32;
+    fn member_type() -> ecs_entity_t {
+        if T::COUNT == 0 {
+            return T::member_type();
+        }
+        unsafe {
+            let mut array_desc: ecs_array_desc_t = std::mem::MaybeUninit::zeroed().assume_init();
+            array_desc.type_ = T::member_type();
+            array_desc.count = T::COUNT;
+            ecs_array_init(*WORLD, &array_desc)
+        }
+    }
+}
+
+// Id of T, registering it on first use
+pub fn component_id<T: Component>() -> ecs_entity_t {
+    if let Some(id) = COMPONENT_IDS.lock().unwrap().get(&TypeId::of::<T>()) {
+        return *id;
+    }
+    // Registered without holding the lock, as register may look up the ids
+    // of nested components
+    let id = T::register();
+    *COMPONENT_IDS.lock().unwrap().entry(TypeId::of::<T>()).or_insert(id)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Entity(ecs_entity_t);
+
+impl Entity {
+    pub const fn from_id(id: ecs_entity_t) -> Self {
+        Entity(id)
+    }
+
+    pub const fn id(self) -> ecs_entity_t {
+        self.0
+    }
+}
+
+impl From<Entity> for ecs_entity_t {
+    fn from(entity: Entity) -> Self {
+        entity.0
+    }
+}
+
+// Handle to the world of the process, flecs_core::WORLD
+pub struct World {
+    // The world isn't thread safe
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl World {
+    // Panics while another World handle is alive, as two handles would allow
+    // aliasing borrows of the same world
+    pub fn new() -> Self {
+        if WORLD_TAKEN.swap(true, Ordering::SeqCst) {
+            panic!("only one World handle can exist at a time");
+        }
+        init();
+        World { _not_send: PhantomData }
+    }
+
+    pub fn entity(&mut self) -> Entity {
+        unsafe { Entity(flecs_entity_create()) }
+    }
+
+    pub fn entity_named(&mut self, name: &str) -> Entity {
+        let name = CString::new(name).unwrap();
+        unsafe { Entity(flecs_entity_create_named(name.as_ptr())) }
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Entity> {
+        let name = CString::new(name).unwrap();
+        match unsafe { flecs_component_lookup(name.as_ptr() as *mut c_char) } {
+            0 => None,
+            id => Some(Entity(id)),
+        }
+    }
+
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        unsafe {
+            let name = ecs_get_name(*WORLD, entity.0);
+            if name.is_null() {
+                return None;
+            }
+            CStr::from_ptr(name).to_str().ok()
+        }
+    }
+
+    pub fn set_name(&mut self, entity: Entity, name: &str) {
+        self.expect_alive(entity);
+        let name = CString::new(name).unwrap();
+        unsafe { flecs_entity_set_name(entity.0, name.as_ptr() as *mut c_char) }
+    }
+
+    pub fn component<T: Component>(&mut self) -> Entity {
+        Entity(component_id::<T>())
+    }
+
+    pub fn tag(&mut self, name: &str) -> Entity {
+        let name = CString::new(name).unwrap();
+        unsafe { Entity(flecs_tag_create(name.as_ptr())) }
+    }
+
+    pub fn is_valid(&self, entity: Entity) -> bool {
+        unsafe { flecs_is_valid(entity.0) }
+    }
+
+    // False once the entity is deleted. Methods that read an entity treat a
+    // deleted one as having nothing, methods that change it panic.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        entity.0 != 0 && unsafe { ecs_is_alive(*WORLD, entity.0) }
+    }
+
+    fn expect_alive(&self, entity: Entity) {
+        assert!(self.is_alive(entity), "entity {} is not alive", entity.0);
+    }
+
+    // Deleting an entity that is already deleted does nothing
+    pub fn delete(&mut self, entity: Entity) {
+        if self.is_alive(entity) {
+            unsafe { flecs_delete_entity(entity.0) }
+        }
+    }
+
+    // Adds T with its default value
+    pub fn add<T: Component>(&mut self, entity: Entity) {
+        self.expect_alive(entity);
+        unsafe { flecs_entity_add_component(entity.0, component_id::<T>()) }
+    }
+
+    pub fn set<T: Component>(&mut self, entity: Entity, value: T) {
+        self.expect_alive(entity);
+        let component = component_id::<T>();
+        unsafe {
+            if std::mem::size_of::<T>() == 0 {
+                flecs_entity_add_tag(entity.0, component);
+                return;
+            }
+            *(flecs_entity_get_component(entity.0, component) as *mut T) = value;
+            flecs_entity_component_modified(entity.0, component);
+        }
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.is_alive(entity) && unsafe { flecs_entity_has_component(entity.0, component_id::<T>()) }
+    }
+
+    // Reads without ecs_get_mut_id, which would override a component the
+    // entity inherits through IsA and so move it to another table
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        if !self.has::<T>(entity) {
+            return None;
+        }
+        if std::mem::size_of::<T>() == 0 {
+            return Some(unsafe { NonNull::dangling().as_ref() });
+        }
+        unsafe { (ecs_get_id(*WORLD, entity.0, component_id::<T>()) as *const T).as_ref() }
+    }
+
+    // An inherited component is overridden, so that the entity gets its own
+    // copy to change. Changes made through the reference don't run OnSet
+    // observers, call modified for that.
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.has::<T>(entity) {
+            return None;
+        }
+        if std::mem::size_of::<T>() == 0 {
+            return Some(unsafe { NonNull::dangling().as_mut() });
+        }
+        unsafe { (flecs_entity_get_component(entity.0, component_id::<T>()) as *mut T).as_mut() }
+    }
+
+    pub fn modified<T: Component>(&mut self, entity: Entity) {
+        self.expect_alive(entity);
+        unsafe { flecs_entity_component_modified(entity.0, component_id::<T>()) }
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) {
+        self.expect_alive(entity);
+        unsafe { flecs_entity_remove_component(entity.0, component_id::<T>()) }
+    }
+
+    // Singletons are stored on the entity of their own component
+    pub fn set_singleton<T: Component>(&mut self, value: T) {
+        self.set(Entity(component_id::<T>()), value)
+    }
+
+    pub fn has_singleton<T: Component>(&self) -> bool {
+        self.has::<T>(Entity(component_id::<T>()))
+    }
+
+    pub fn get_singleton<T: Component>(&self) -> Option<&T> {
+        self.get(Entity(component_id::<T>()))
+    }
+
+    pub fn get_singleton_mut<T: Component>(&mut self) -> Option<&mut T> {
+        self.get_mut(Entity(component_id::<T>()))
+    }
+
+    pub fn singleton_modified<T: Component>(&mut self) {
+        self.modified::<T>(Entity(component_id::<T>()))
+    }
+
+    pub fn remove_singleton<T: Component>(&mut self) {
+        self.remove::<T>(Entity(component_id::<T>()))
+    }
+
+    pub fn add_id(&mut self, entity: Entity, id: Entity) {
+        self.expect_alive(entity);
+        unsafe { flecs_entity_add_tag(entity.0, id.0) }
+    }
+
+    pub fn has_id(&self, entity: Entity, id: Entity) -> bool {
+        self.is_alive(entity) && unsafe { flecs_entity_has_component(entity.0, id.0) }
+    }
+
+    pub fn remove_id(&mut self, entity: Entity, id: Entity) {
+        self.expect_alive(entity);
+        unsafe { flecs_entity_remove_component(entity.0, id.0) }
+    }
+
+    pub fn add_pair(&mut self, entity: Entity, relationship: Entity, target: Entity) {
+        self.expect_alive(entity);
+        self.expect_alive(relationship);
+        self.expect_alive(target);
+        unsafe { flecs_entity_add_pair(entity.0, relationship.0, target.0) }
+    }
+
+    pub fn has_pair(&self, entity: Entity, relationship: Entity, target: Entity) -> bool {
+        self.is_alive(entity) && unsafe { flecs_entity_has_pair(entity.0, relationship.0, target.0) }
+    }
+
+    pub fn remove_pair(&mut self, entity: Entity, relationship: Entity, target: Entity) {
+        self.expect_alive(entity);
+        unsafe { flecs_entity_remove_pair(entity.0, relationship.0, target.0) }
+    }
+
+    pub fn target(&self, entity: Entity, relationship: Entity, index: i32) -> Option<Entity> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        match unsafe { flecs_entity_get_target(entity.0, relationship.0, index) } {
+            0 => None,
+            target => Some(Entity(target)),
+        }
+    }
+
+    pub fn child_of(&mut self, entity: Entity, parent: Entity) {
+        self.expect_alive(entity);
+        self.expect_alive(parent);
+        unsafe { flecs_entity_child_of(entity.0, parent.0) }
+    }
+
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        match unsafe { flecs_entity_get_parent(entity.0) } {
+            0 => None,
+            parent => Some(Entity(parent)),
+        }
+    }
+
+    // Runs all systems once. Returns false when the application should quit.
+    pub fn progress(&mut self, delta_time: f32) -> bool {
+        unsafe { flecs_progress(delta_time) }
+    }
+
+    pub fn query<Q: QueryData>(&mut self) -> Query<Q> {
+        unsafe {
+            let desc = flecs_query_create();
+            add_terms::<Q>(&mut (*desc).filter);
+            let query = flecs_query_build(desc);
+            drop(Box::from_raw(desc));
+            Query { query, _data: PhantomData }
+        }
+    }
+
+    // System that runs f for every matched entity on each progress
+    pub fn system<Q, F>(&mut self, mut f: F) -> System
+    where
+        Q: QueryData,
+        F: FnMut(Entity, Q::Item<'_>) + Send + 'static,
+    {
+        self.system_iter::<Q, _>(move |iter| unsafe { each_row::<Q, F>(iter, &mut f) })
+    }
+
+    // System that runs f once per matched table
+    pub fn system_iter<Q, F>(&mut self, f: F) -> System
+    where
+        Q: QueryData,
+        F: FnMut(&mut Iter) + Send + 'static,
+    {
+        unsafe {
+            let desc = system_create_closure(f);
+            add_terms::<Q>(&mut (*flecs_query_from_system_desc(desc)).filter);
+            let entity = flecs_system_build(desc);
+            drop(Box::from_raw(desc));
+            System { entity: Entity(entity) }
+        }
+    }
+
+    // Observer that runs f for every entity an event is emitted for
+    pub fn observer<Q, F>(&mut self, events: &[Event], mut f: F) -> Observer
+    where
+        Q: QueryData,
+        F: FnMut(Entity, Q::Item<'_>) + Send + 'static,
+    {
+        self.observer_iter::<Q, _>(events, move |iter| unsafe { each_row::<Q, F>(iter, &mut f) })
+    }
+
+    pub fn observer_iter<Q, F>(&mut self, events: &[Event], f: F) -> Observer
+    where
+        Q: QueryData,
+        F: FnMut(&mut Iter) + Send + 'static,
+    {
+        unsafe {
+            let desc = observer_create_closure(f);
+            for event in events {
+                assert!(flecs_observer_add_event(desc, *event as u8), "too many observer events");
+            }
+            add_terms::<Q>(flecs_filter_from_observer_desc(desc));
+            let entity = flecs_observer_build(desc);
+            drop(Box::from_raw(desc));
+            Observer { entity: Entity(entity) }
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World::new()
+    }
+}
+
+impl Drop for World {
+    fn drop(&mut self) {
+        WORLD_TAKEN.store(false, Ordering::SeqCst);
+    }
+}
+
+// Pointer to the data of one field of the current table
+#[derive(Clone, Copy)]
+pub struct Column {
+    ptr: *mut u8,
+    is_self: bool,
+}
+
+impl Column {
+    unsafe fn new<T: Component>(iter: &Iter, term_index: i32) -> Self {
+        if !iter.field_is_set(term_index) {
+            return Column { ptr: std::ptr::null_mut(), is_self: true };
+        }
+        let ptr = if std::mem::size_of::<T>() == 0 {
+            NonNull::<T>::dangling().as_ptr()
+        } else {
+            iter.field::<T>(term_index).map_or(std::ptr::null_mut(), |field| field.as_mut_ptr())
+        };
+        Column { ptr: ptr as *mut u8, is_self: iter.field_is_self(term_index) }
+    }
+
+    // Shared fields have one value for all rows
+    unsafe fn get<T>(self, row: usize) -> *mut T {
+        (self.ptr as *mut T).add(if self.is_self { row } else { 0 })
+    }
+}
+
+// One term of a query: &T, &mut T, Option<&T>, Option<&mut T> or Singleton<&T>
+pub trait Term {
+    type Component: Component;
+    type Item<'w>;
+    const MUTABLE: bool;
+    const OPTIONAL: bool;
+    const SINGLETON: bool = false;
+    /// # Safety
+    /// column has to be the column of this term in the table being iterated,
+    /// and row one of its rows. The item must not outlive the iteration.
+    unsafe fn item<'w>(column: Column, row: usize) -> Self::Item<'w>;
+}
+
+impl<T: Component> Term for &T {
+    type Component = T;
+    type Item<'w> = &'w T;
+    const MUTABLE: bool = false;
+    const OPTIONAL: bool = false;
+    unsafe fn item<'w>(column: Column, row: usize) -> &'w T {
+        &*column.get::<T>(row)
+    }
+}
+
+impl<T: Component> Term for &mut T {
+    type Component = T;
+    type Item<'w> = &'w mut T;
+    const MUTABLE: bool = true;
+    const OPTIONAL: bool = false;
+    unsafe fn item<'w>(column: Column, row: usize) -> &'w mut T {
+        &mut *column.get::<T>(row)
+    }
+}
+
+impl<T: Component> Term for Option<&T> {
+    type Component = T;
+    type Item<'w> = Option<&'w T>;
+    const MUTABLE: bool = false;
+    const OPTIONAL: bool = true;
+    unsafe fn item<'w>(column: Column, row: usize) -> Option<&'w T> {
+        (!column.ptr.is_null()).then(|| &*column.get::<T>(row))
+    }
+}
+
+impl<T: Component> Term for Option<&mut T> {
+    type Component = T;
+    type Item<'w> = Option<&'w mut T>;
+    const MUTABLE: bool = true;
+    const OPTIONAL: bool = true;
+    unsafe fn item<'w>(column: Column, row: usize) -> Option<&'w mut T> {
+        (!column.ptr.is_null()).then(|| &mut *column.get::<T>(row))
+    }
+}
+
+// Matches the singleton of T rather than a component of the matched entity
+pub struct Singleton<T>(PhantomData<T>);
+
+impl<T: Component> Term for Singleton<&T> {
+    type Component = T;
+    type Item<'w> = &'w T;
+    const MUTABLE: bool = false;
+    const OPTIONAL: bool = false;
+    const SINGLETON: bool = true;
+    unsafe fn item<'w>(column: Column, row: usize) -> &'w T {
+        &*column.get::<T>(row)
+    }
+}
+
+// Tuple of terms a query matches, e.g. (&Position, &mut Velocity)
+pub trait QueryData: 'static {
+    type Item<'w>;
+    type Columns: Copy;
+    /// # Safety
+    /// filter_desc has to point to a filter description without terms
+    unsafe fn add_terms(filter_desc: *mut ecs_filter_desc_t);
+    /// # Safety
+    /// iter has to iterate a query whose terms were added by add_terms
+    unsafe fn columns(iter: &Iter) -> Self::Columns;
+    /// # Safety
+    /// columns have to come from columns for the table being iterated, and
+    /// row has to be one of its rows. Items must not outlive the iteration.
+    unsafe fn item<'w>(columns: Self::Columns, row: usize) -> Self::Item<'w>;
+}
+
+unsafe fn add_term<T: Term>(filter_desc: *mut ecs_filter_desc_t, index: usize) {
+    let mut id = component_id::<T::Component>();
+    if T::SINGLETON {
+        flecs_filter_with_singleton(filter_desc, index as u8, &mut id, 1);
+    } else {
+        flecs_filter_with(filter_desc, index as u8, &mut id, 1);
+    }
+    let term = &mut (*filter_desc).terms[index];
+    if T::MUTABLE {
+        // Mutable references may not be shared between entities
+        term.inout = ecs_inout_kind_t_EcsInOut;
+        term.src.flags = EcsSelf;
+    } else {
+        term.inout = ecs_inout_kind_t_EcsIn;
+    }
+    if T::OPTIONAL {
+        term.oper = ecs_oper_kind_t_EcsOptional;
+    }
+}
+
+// Panics when a component is matched by more than one term while one of them
+// is mutable, since the query would hand out aliasing references
+unsafe fn add_terms<Q: QueryData>(filter_desc: *mut ecs_filter_desc_t) {
+    Q::add_terms(filter_desc);
+    let terms = &(*filter_desc).terms;
+    for (index, term) in terms.iter().enumerate().take_while(|(_, term)| term.id != 0) {
+        for other in terms[index + 1..].iter().take_while(|other| other.id != 0) {
+            let mutable = term.inout == ecs_inout_kind_t_EcsInOut || other.inout == ecs_inout_kind_t_EcsInOut;
+            assert!(!(mutable && term.id == other.id), "component borrowed mutably by more than one term");
+        }
+    }
+}
+
+macro_rules! impl_query_data {
+    ($($T:ident $index:tt),+) => {
+        impl<$($T: Term + 'static),+> QueryData for ($($T,)+) {
+            type Item<'w> = ($($T::Item<'w>,)+);
+            type Columns = ($(impl_query_data!(@column $T),)+);
+
+            unsafe fn add_terms(filter_desc: *mut ecs_filter_desc_t) {
+                $(add_term::<$T>(filter_desc, $index);)+
+            }
+
+            unsafe fn columns(iter: &Iter) -> Self::Columns {
+                ($(Column::new::<$T::Component>(iter, $index + 1),)+)
+            }
+
+            unsafe fn item<'w>(columns: Self::Columns, row: usize) -> Self::Item<'w> {
+                ($($T::item(columns.$index, row),)+)
+            }
+        }
+    };
+    (@column $T:ident) => { Column };
+}
+
+impl_query_data!(A 0);
+impl_query_data!(A 0, B 1);
+impl_query_data!(A 0, B 1, C 2);
+impl_query_data!(A 0, B 1, C 2, D 3);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_query_data!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+unsafe fn each_row<Q, F>(iter: &mut Iter, f: &mut F)
+where
+    Q: QueryData,
+    F: FnMut(Entity, Q::Item<'_>),
+{
+    let columns = Q::columns(iter);
+    for (row, entity) in iter.entities().iter().enumerate() {
+        f(Entity(*entity), Q::item(columns, row));
+    }
+}
+
+// Cached query, destroyed when dropped
+pub struct Query<Q: QueryData> {
+    query: *mut ecs_query_t,
+    _data: PhantomData<fn() -> Q>,
+}
+
+impl<Q: QueryData> Query<Q> {
+    pub fn as_ptr(&self) -> *mut ecs_query_t {
+        self.query
+    }
+
+    pub fn iter<'w>(&'w self, world: &'w mut World) -> QueryIter<'w, Q> {
+        QueryIter {
+            iter: unsafe { flecs_query_iter(self.query) },
+            columns: None,
+            entities: std::ptr::null(),
+            row: 0,
+            count: 0,
+            done: false,
+            _world: PhantomData,
+        }
+    }
+
+    pub fn each<F>(&self, world: &mut World, mut f: F)
+    where
+        F: FnMut(Entity, Q::Item<'_>),
+    {
+        for (entity, item) in self.iter(world) {
+            f(entity, item);
+        }
+    }
+}
+
+impl<Q: QueryData> Drop for Query<Q> {
+    fn drop(&mut self) {
+        unsafe { flecs_query_destroy(self.query) }
+    }
+}
+
+// Iterator over the entities matched by a query. Finishes and frees the
+// flecs iterator when dropped.
+pub struct QueryIter<'w, Q: QueryData> {
+    iter: *mut ecs_iter_t,
+    columns: Option<Q::Columns>,
+    entities: *const ecs_entity_t,
+    row: usize,
+    count: usize,
+    done: bool,
+    _world: PhantomData<&'w mut World>,
+}
+
+impl<'w, Q: QueryData> Iterator for QueryIter<'w, Q> {
+    type Item = (Entity, Q::Item<'w>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while self.row == self.count {
+                if self.done || !flecs_query_next(self.iter) {
+                    self.done = true;
+                    return None;
+                }
+                let mut count = 0;
+                self.entities = flecs_iter_entities(self.iter, &mut count);
+                self.count = count as usize;
+                self.row = 0;
+                self.columns = Some(Q::columns(&Iter::from_raw(self.iter)));
+            }
+            let row = self.row;
+            self.row += 1;
+            Some((Entity(*self.entities.add(row)), Q::item(self.columns?, row)))
+        }
+    }
+}
+
+impl<Q: QueryData> Drop for QueryIter<'_, Q> {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.done {
+                flecs_iter_fini(self.iter);
+            }
+            flecs_iter_destroy(self.iter);
+        }
+    }
+}
+
+// System entity. Deleting it drops the closure it runs.
+pub struct System {
+    entity: Entity,
+}
+
+impl System {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn delete(self, world: &mut World) {
+        world.delete(self.entity);
+    }
+}
+
+// Observer entity. Deleting it drops the closure it runs.
+pub struct Observer {
+    entity: Entity,
+}
+
+impl Observer {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn delete(self, world: &mut World) {
+        world.delete(self.entity);
+    }
+}
@@ -5,22 +5,42 @@
 #![allow(non_snake_case)]
 #![allow(deref_nullptr)]
 #![allow(improper_ctypes)]
+// The flecs_* exports are the C ABI of the crate and share one safety
+// contract: pointers have to come from other exports, or point to memory laid
+// out as flecs_core.h declares. That's why missing_safety_doc is allowed here
+// instead of each export repeating it. The ecs module turns it back on.
+#![allow(clippy::missing_safety_doc)]
 // #![feature(thread_id_value)]
 
 pub mod bindings {
     include!("./bindings.rs");
 }
 pub use bindings::*;
-use toxoid_api::make_c_string;
+
+pub mod ecs;
 
 use std::mem::MaybeUninit;
-use std::collections::HashMap;
 #[cfg(feature = "multithread")]
 use std::thread::JoinHandle;
-use core::ffi::{c_char, c_void};
+use core::ffi::{c_char, c_int, c_void};
 use once_cell::sync::Lazy;
 
-pub static mut WORLD: Lazy<*mut bindings::ecs_world_t> = Lazy::new(|| unsafe { ecs_init() });
+pub static mut WORLD: Lazy<*mut bindings::ecs_world_t> = Lazy::new(|| unsafe { world_init() });
+
+// Creates the world with the log and abort hooks installed, so that errors,
+// including the ones flecs logs while initializing, are recorded for
+// flecs_last_error
+unsafe fn world_init() -> *mut ecs_world_t {
+    WORLD_CREATED.store(true, std::sync::atomic::Ordering::Relaxed);
+    ecs_os_set_api_defaults();
+    let mut api = ecs_os_api;
+    allocator_install(&mut api);
+    LOG_CONSOLE = api.log_;
+    api.log_ = Some(log_trampoline);
+    api.abort_ = Some(abort_trampoline);
+    ecs_os_set_api(&mut api);
+    ecs_init()
+}
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -30,6 +50,9 @@ pub struct ecs_struct_desc_t {
 }
 
 extern "C" {
+    // The generated bindings declare malloc with a 64-bit size, which is wrong on wasm32
+    #[allow(clashing_extern_declarations)]
+    pub fn malloc(size: usize) -> *mut c_void;
     pub fn free(ptr: *mut c_void);
     #[cfg(feature = "multithread")]
     pub fn pthread_self() -> i32;
@@ -54,9 +77,25 @@ pub enum Type {
     Array,
     U32Array,
     F32Array,
+    // Growable vectors owned by the component, in the order of their element types
+    U8Vector,
+    U16Vector,
+    U32Vector,
+    U64Vector,
+    I8Vector,
+    I16Vector,
+    I32Vector,
+    I64Vector,
+    F32Vector,
+    F64Vector,
+    BoolVector,
+    StringVector,
 }
 
-// Generic function to iterate over an ecs_vector_t
+/// Generic function to iterate over an ecs_vector_t
+///
+/// # Safety
+/// vector has to point to a live vector whose elements are of type T
 pub unsafe fn ecs_vector_each<T, F>(vector: *const ecs_vec_t, mut f: F)
 where
     T: Sized,
@@ -72,6 +111,65 @@ where
     }
 }
 
+// Codes of the relationship traits taken by flecs_relationship_add_trait
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RelationshipTrait {
+    Exclusive,
+    Symmetric,
+    Transitive,
+    Reflexive,
+    Acyclic,
+    Union,
+    OneOf,
+    Tag,
+}
+
+// Codes of the cleanup conditions and actions taken by
+// flecs_entity_add_cleanup_policy
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CleanupCondition {
+    OnDelete,
+    OnDeleteTarget,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CleanupAction {
+    Remove,
+    Delete,
+    Panic,
+}
+
+unsafe fn get_relationship_trait(relationship_trait: u8) -> Option<ecs_entity_t> {
+    match relationship_trait {
+        0 => Some(EcsExclusive),
+        1 => Some(EcsSymmetric),
+        2 => Some(EcsTransitive),
+        3 => Some(EcsReflexive),
+        4 => Some(EcsAcyclic),
+        5 => Some(EcsUnion),
+        6 => Some(EcsOneOf),
+        7 => Some(EcsTag),
+        _ => None,
+    }
+}
+
+unsafe fn get_cleanup_condition(condition: u8) -> Option<ecs_entity_t> {
+    match condition {
+        0 => Some(EcsOnDelete),
+        1 => Some(EcsOnDeleteTarget),
+        _ => None,
+    }
+}
+
+unsafe fn get_cleanup_action(action: u8) -> Option<ecs_entity_t> {
+    match action {
+        0 => Some(EcsRemove),
+        1 => Some(EcsDelete),
+        2 => Some(EcsPanic),
+        _ => None,
+    }
+}
+
 unsafe fn get_member_type(member_type: u8) -> ecs_entity_t {
     match member_type {
         0 => FLECS_IDecs_u8_tID_,
@@ -86,10 +184,74 @@ unsafe fn get_member_type(member_type: u8) -> ecs_entity_t {
         9 => FLECS_IDecs_f64_tID_,
         10 => FLECS_IDecs_bool_tID_,
         11 => FLECS_IDecs_string_tID_,
+        13 | 14 => get_array_type(member_type),
+        15..=26 => get_vector_type(member_type - Type::U8Vector as u8),
         _ => FLECS_IDecs_uptr_tID_,
     }
 }
 
+// Vector types are created the first time a member uses them
+static mut VECTOR_TYPES: [ecs_entity_t; 12] = [0; 12];
+
+unsafe fn get_vector_type(element_type: u8) -> ecs_entity_t {
+    let index = element_type as usize;
+    if VECTOR_TYPES[index] == 0 {
+        let mut desc: ecs_vector_desc_t = MaybeUninit::zeroed().assume_init();
+        desc.type_ = get_member_type(element_type);
+        VECTOR_TYPES[index] = ecs_vector_init(*WORLD, &desc);
+    }
+    VECTOR_TYPES[index]
+}
+
+// U32Array and F32Array members point to an array that starts with its
+// length. They are reflected as opaque types that serialize as vectors.
+static mut ARRAY_TYPES: [ecs_entity_t; 2] = [0; 2];
+
+unsafe fn get_array_type(member_type: u8) -> ecs_entity_t {
+    let index = (member_type - Type::U32Array as u8) as usize;
+    if ARRAY_TYPES[index] == 0 {
+        let world = *WORLD;
+        let mut component_desc: ecs_component_desc_t = MaybeUninit::zeroed().assume_init();
+        component_desc.type_.size = std::mem::size_of::<*mut c_void>() as i32;
+        component_desc.type_.alignment = std::mem::align_of::<*mut c_void>() as i32;
+        let mut desc: ecs_opaque_desc_t = MaybeUninit::zeroed().assume_init();
+        desc.entity = ecs_component_init(world, &component_desc);
+        if member_type == Type::U32Array as u8 {
+            desc.type_.as_type = get_vector_type(Type::U32 as u8);
+            desc.type_.serialize = Some(u32array_serialize);
+        } else {
+            desc.type_.as_type = get_vector_type(Type::F32 as u8);
+            desc.type_.serialize = Some(f32array_serialize);
+        }
+        ARRAY_TYPES[index] = ecs_opaque_init(world, &desc);
+    }
+    ARRAY_TYPES[index]
+}
+
+unsafe extern "C" fn u32array_serialize(ser: *const ecs_serializer_t, src: *const c_void) -> c_int {
+    let array = *(src as *const *const u32);
+    let length = if array.is_null() { 0 } else { *array as usize };
+    array_serialize(ser, FLECS_IDecs_u32_tID_, array as *const c_void, length)
+}
+
+unsafe extern "C" fn f32array_serialize(ser: *const ecs_serializer_t, src: *const c_void) -> c_int {
+    let array = *(src as *const *const f32);
+    let length = if array.is_null() { 0 } else { *array as usize };
+    array_serialize(ser, FLECS_IDecs_f32_tID_, array as *const c_void, length)
+}
+
+// Serializes the 4 byte elements that follow the length of an array
+unsafe fn array_serialize(ser: *const ecs_serializer_t, element_type: ecs_entity_t, array: *const c_void, length: usize) -> c_int {
+    let value = (*ser).value.unwrap();
+    for index in 1..=length {
+        let result = value(ser, element_type, (array as *const u32).add(index) as *const c_void);
+        if result != 0 {
+            return result;
+        }
+    }
+    0
+}
+
 pub fn init() {
     #[cfg(feature = "multithread")]
     unsafe {
@@ -107,10 +269,15 @@ pub fn init() {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn flecs_init() {
+    init();
+}
+
 #[cfg(feature = "multithread")]
 #[no_mangle]
 pub unsafe extern "C" fn flecs_os_api_task_new(optional_callback: Option<unsafe extern "C" fn(*mut c_void) -> *mut c_void>, _ctx: *mut c_void) -> usize {
-    println!("From flecs_os_api_task_new");
+    log::trace!("flecs_os_api_task_new");
     let callback = optional_callback.unwrap();
     let ctx_as_usize: usize = _ctx as usize;
     let handle = std::thread::spawn(move || {
@@ -127,7 +294,7 @@ pub unsafe extern "C" fn flecs_os_api_task_new(optional_callback: Option<unsafe
 #[cfg(feature = "multithread")]
 #[no_mangle]
 pub unsafe extern "C" fn flecs_os_api_task_join(handle: usize) -> *mut c_void {
-    println!("From flecs_os_api_task_join");
+    log::trace!("flecs_os_api_task_join");
      // Convert back to the original Rust JoinHandle type
      let handle: Box<JoinHandle<()>> = Box::from_raw(handle as *mut JoinHandle<()>);
 
@@ -139,7 +306,7 @@ pub unsafe extern "C" fn flecs_os_api_task_join(handle: usize) -> *mut c_void {
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_create(
+pub unsafe extern "C" fn flecs_component_create(
     component_name: *const c_char,
     member_names: *const *const c_char,
     member_names_count: u32,
@@ -161,9 +328,9 @@ pub unsafe fn flecs_component_create(
     struct_desc.members = [member; 32usize];
 
     let member_names =
-        std::slice::from_raw_parts(member_names as *const *const c_char, member_names_count as usize);
+        std::slice::from_raw_parts(member_names, member_names_count as usize);
     let member_types =
-        std::slice::from_raw_parts(member_types as *const u8, member_names_count as usize);
+        std::slice::from_raw_parts(member_types, member_names_count as usize);
 
     // Iterate through member names
     for (index, member_name) in member_names.iter().enumerate() {
@@ -176,11 +343,35 @@ pub unsafe fn flecs_component_create(
         struct_desc.members[index] = member;
     }
 
-    ecs_struct_init(world, &struct_desc)
+    let component = ecs_struct_init(world, &struct_desc);
+    if component != 0 {
+        owned_members_init(component, member_types);
+    }
+    component
+}
+
+// Component without reflection data, for types whose layout is only known
+// to the caller
+#[no_mangle]
+pub unsafe extern "C" fn flecs_component_create_sized(
+    component_name: *const c_char,
+    size: i32,
+    alignment: i32,
+) -> ecs_entity_t {
+    let world = *WORLD;
+
+    let mut ent_desc: ecs_entity_desc_t = MaybeUninit::zeroed().assume_init();
+    ent_desc.name = component_name;
+    ent_desc.symbol = component_name;
+    let mut component_desc: ecs_component_desc_t = MaybeUninit::zeroed().assume_init();
+    component_desc.entity = ecs_entity_init(world, &ent_desc);
+    component_desc.type_.size = size;
+    component_desc.type_.alignment = alignment;
+    ecs_component_init(world, &component_desc)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_tag_create(tag_name: *const c_char) -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_tag_create(tag_name: *const c_char) -> ecs_entity_t {
     let world = *WORLD;
 
     // Create component entity description
@@ -192,27 +383,27 @@ pub unsafe fn flecs_tag_create(tag_name: *const c_char) -> ecs_entity_t {
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get(name: *const c_char) -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_component_get(name: *const c_char) -> ecs_entity_t {
     let world = *WORLD;
     let component_entity: ecs_entity_t = ecs_lookup(world, name);
     component_entity
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_create() -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_entity_create() -> ecs_entity_t {
     let world = *WORLD;
     let ent_desc: ecs_entity_desc_t = MaybeUninit::zeroed().assume_init();
     ecs_entity_init(world, &ent_desc)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_prefab_create() -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_prefab_create() -> ecs_entity_t {
     let world = *WORLD;
     ecs_new_w_id(world, EcsPrefab)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_prefab_instance(prefab: ecs_entity_t) -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_prefab_instance(prefab: ecs_entity_t) -> ecs_entity_t {
     let world = *WORLD;
     let ent_desc: ecs_entity_desc_t = MaybeUninit::zeroed().assume_init();
     let pair = ecs_make_pair(EcsIsA, prefab);
@@ -220,7 +411,7 @@ pub unsafe fn flecs_prefab_instance(prefab: ecs_entity_t) -> ecs_entity_t {
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_create_named(name: *const c_char) -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_entity_create_named(name: *const c_char) -> ecs_entity_t {
     let world = *WORLD;
     let mut ent_desc: ecs_entity_desc_t = MaybeUninit::zeroed().assume_init();
     ent_desc.name = name;
@@ -228,7 +419,7 @@ pub unsafe fn flecs_entity_create_named(name: *const c_char) -> ecs_entity_t {
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_create_bulk(count: i32) -> *const ecs_entity_t {
+pub unsafe extern "C" fn flecs_entity_create_bulk(count: i32) -> *const ecs_entity_t {
     let world = *WORLD;
     let mut ent_desc: ecs_bulk_desc_t = MaybeUninit::zeroed().assume_init();
     ent_desc.count = count;
@@ -236,106 +427,468 @@ pub unsafe fn flecs_entity_create_bulk(count: i32) -> *const ecs_entity_t {
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_create_bulk_components(
+pub unsafe extern "C" fn flecs_entity_create_bulk_components(
     entity_count: i32,
     component_count: u32,
-    components: *const u32,
+    components: *const ecs_entity_t,
 ) -> *const ecs_entity_t {
     let world = *WORLD;
-    let components = std::slice::from_raw_parts(components as *const ecs_entity_t, component_count as usize);
+    // The ids array is 0-terminated, so one slot has to stay free
+    if component_count >= FLECS_ID_DESC_MAX {
+        error_set(ECS_INVALID_PARAMETER, "too many components for a bulk create");
+        return std::ptr::null();
+    }
+    let components = std::slice::from_raw_parts(components, component_count as usize);
     let mut ent_desc: ecs_bulk_desc_t = MaybeUninit::zeroed().assume_init();
     ent_desc.count = entity_count;
     for (index, component) in components.iter().enumerate() {
-        ent_desc.ids[index] = *component as ecs_entity_t;
+        ent_desc.ids[index] = *component;
     }
 
     ecs_bulk_init(world, &ent_desc)
 }
 
+// Spawns entity_count entities and initializes their components from column
+// buffers. data[i] points to entity_count contiguous values of components[i],
+// or is null for tags and components that should stay default constructed.
+// Returns a malloc'd copy of the new entity ids which the caller releases
+// with m_free, since the array returned by ecs_bulk_init is owned by flecs
+// and invalidated by the next entity creation.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_create_bulk_data(
+    entity_count: i32,
+    component_count: u32,
+    components: *const ecs_entity_t,
+    data: *const *mut c_void,
+) -> *mut ecs_entity_t {
+    let world = *WORLD;
+    if entity_count <= 0 || component_count >= FLECS_ID_DESC_MAX {
+        error_set(ECS_INVALID_PARAMETER, "invalid entity or component count for a bulk create");
+        return std::ptr::null_mut();
+    }
+    if component_count > 0 && (components.is_null() || data.is_null()) {
+        error_set(ECS_INVALID_PARAMETER, "components and data can't be null for a bulk create");
+        return std::ptr::null_mut();
+    }
+    // from_raw_parts needs a non-null pointer even for no elements
+    let (components, data): (&[ecs_entity_t], &[*mut c_void]) = if component_count == 0 {
+        (&[], &[])
+    } else {
+        (
+            std::slice::from_raw_parts(components, component_count as usize),
+            std::slice::from_raw_parts(data, component_count as usize),
+        )
+    };
+
+    let mut ids: [ecs_id_t; FLECS_ID_DESC_MAX as usize] = [0; FLECS_ID_DESC_MAX as usize];
+    let mut column_data: [*mut c_void; FLECS_ID_DESC_MAX as usize] = [std::ptr::null_mut(); FLECS_ID_DESC_MAX as usize];
+    for (index, component) in components.iter().enumerate() {
+        ids[index] = *component;
+        column_data[index] = data[index];
+    }
+
+    let mut ent_desc: ecs_bulk_desc_t = MaybeUninit::zeroed().assume_init();
+    ent_desc.count = entity_count;
+    ent_desc.ids = ids;
+    ent_desc.data = column_data.as_mut_ptr();
+    let entities = ecs_bulk_init(world, &ent_desc);
+    if entities.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let entities_ptr = malloc(entity_count as usize * std::mem::size_of::<ecs_entity_t>()) as *mut ecs_entity_t;
+    std::ptr::copy_nonoverlapping(entities, entities_ptr, entity_count as usize);
+    entities_ptr
+}
+
+// Singletons live on their own component entity, like with ecs_singleton_*,
+// so query terms with the $ source match them
 #[no_mangle]
-pub unsafe fn flecs_singleton_add(component: ecs_entity_t) {
+pub unsafe extern "C" fn flecs_singleton_add(component: ecs_entity_t) {
     let world = *WORLD;
-    ecs_add_id(world, EcsWorld, component);
+    ecs_add_id(world, component, component);
 }
 
 #[no_mangle]
-pub unsafe fn flecs_singleton_get(component: ecs_entity_t) -> *mut c_void {
+pub unsafe extern "C" fn flecs_singleton_set(component: ecs_entity_t, data: *const c_void) {
     let world = *WORLD;
-    ecs_get_mut_id(world, EcsWorld, component)
+    let type_info = ecs_get_type_info(world, component);
+    if type_info.is_null() {
+        // Tags don't carry data, so there is nothing to copy
+        ecs_add_id(world, component, component);
+        return;
+    }
+    ecs_set_id(world, component, component, (*type_info).size as usize, data);
+}
+
+// Returns null if the singleton isn't set
+#[no_mangle]
+pub unsafe extern "C" fn flecs_singleton_get(component: ecs_entity_t) -> *const c_void {
+    let world = *WORLD;
+    ecs_get_id(world, component, component)
+}
+
+// Adds the singleton if it isn't set. Call flecs_singleton_modified after
+// writing through the pointer to run OnSet observers.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_singleton_get_mut(component: ecs_entity_t) -> *mut c_void {
+    let world = *WORLD;
+    ecs_get_mut_id(world, component, component)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_singleton_has(component: ecs_entity_t) -> bool {
+    let world = *WORLD;
+    ecs_has_id(world, component, component)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_singleton_modified(component: ecs_entity_t) {
+    let world = *WORLD;
+    ecs_modified_id(world, component, component);
 }
 
 #[no_mangle]
-pub unsafe fn flecs_singleton_remove(component: ecs_entity_t) {
+pub unsafe extern "C" fn flecs_singleton_remove(component: ecs_entity_t) {
     let world = *WORLD;
-    ecs_remove_id(world, EcsWorld, component);
+    ecs_remove_id(world, component, component);
+}
+
+// flecs asserts instead of failing when getting the data of an id that has
+// none, so the getters check first. Records the error if the entity isn't
+// alive or the id doesn't carry data.
+unsafe fn data_id_check(world: *const ecs_world_t, entity: ecs_entity_t, id: ecs_id_t) -> bool {
+    if !ecs_is_alive(world, entity) {
+        error_set(ECS_INVALID_PARAMETER, "entity is not alive");
+        return false;
+    }
+    if !ecs_id_is_valid(world, id) || ecs_get_type_info(world, id).is_null() {
+        error_set(ECS_INVALID_PARAMETER, "id is not a component that carries data");
+        return false;
+    }
+    true
 }
 
+// Returns null if the entity isn't alive or the id doesn't carry data
 #[no_mangle]
-pub unsafe fn flecs_entity_get_component(entity: ecs_entity_t, component: ecs_entity_t) -> *mut c_void {
+pub unsafe extern "C" fn flecs_entity_get_component(entity: ecs_entity_t, component: ecs_entity_t) -> *mut c_void {
     let world = *WORLD;
+    if !data_id_check(world, entity, component) {
+        return std::ptr::null_mut();
+    }
     ecs_get_mut_id(world, entity, component)
 }
 
+// Runs OnSet observers after the component was written through
+// flecs_entity_get_component
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_component_modified(entity: ecs_entity_t, component: ecs_entity_t) {
+    let world = *WORLD;
+    ecs_modified_id(world, entity, component);
+}
+
+// Refs cache where an entity stores a component, so getting it again skips
+// the lookup and still works after the entity moved to another table.
+// Returns null if the entity isn't alive; release with flecs_ref_destroy.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_ref_create(entity: ecs_entity_t, component: ecs_entity_t) -> *mut ecs_ref_t {
+    let world = *WORLD;
+    if !ecs_is_alive(world, entity) {
+        error_set(ECS_INVALID_PARAMETER, "entity is not alive");
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(ecs_ref_init_id(world, entity, component)))
+}
+
+// Returns null if the entity doesn't have the component or was deleted
+#[no_mangle]
+pub unsafe extern "C" fn flecs_ref_get(component_ref: *mut ecs_ref_t) -> *mut c_void {
+    let world = *WORLD;
+    // The ref points at the entity's record, which a recycled id reuses
+    if !ecs_is_alive(world, (*component_ref).entity) {
+        return std::ptr::null_mut();
+    }
+    ecs_ref_get_id(world, component_ref, (*component_ref).id)
+}
+
+// Resyncs the cached table record, so a later flecs_ref_get doesn't have to
+#[no_mangle]
+pub unsafe extern "C" fn flecs_ref_update(component_ref: *mut ecs_ref_t) {
+    let world = *WORLD;
+    if ecs_is_alive(world, (*component_ref).entity) {
+        ecs_ref_update(world, component_ref);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_ref_destroy(component_ref: *mut ecs_ref_t) {
+    drop(Box::from_raw(component_ref));
+}
+
 #[no_mangle]
-pub unsafe fn flecs_entity_add_component(entity: ecs_entity_t, component: ecs_entity_t) {
+pub unsafe extern "C" fn flecs_entity_add_component(entity: ecs_entity_t, component: ecs_entity_t) {
     let world = *WORLD;
     ecs_add_id(world, entity, component);
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_remove_component(entity: ecs_entity_t, component: ecs_entity_t) {
+pub unsafe extern "C" fn flecs_entity_remove_component(entity: ecs_entity_t, component: ecs_entity_t) {
     let world = *WORLD;
     ecs_remove_id(world, entity, component)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_add_tag(entity: ecs_entity_t, tag: ecs_entity_t) {
+pub unsafe extern "C" fn flecs_entity_add_tag(entity: ecs_entity_t, tag: ecs_entity_t) {
     let world = *WORLD;
     ecs_add_id(world, entity, tag);
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_child_of(entity: ecs_entity_t, parent: ecs_entity_t) {
+pub unsafe extern "C" fn flecs_entity_child_of(entity: ecs_entity_t, parent: ecs_entity_t) {
     let world = *WORLD;
+    if !ecs_is_alive(world, parent) {
+        error_set(ECS_INVALID_PARAMETER, "parent is not alive");
+        return;
+    }
     let pair = ecs_make_pair(EcsChildOf, parent);
     ecs_add_id(world, entity, pair);
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_children(parent: ecs_entity_t) -> *mut ecs_iter_t {
+pub unsafe extern "C" fn flecs_entity_children(parent: ecs_entity_t) -> *mut ecs_iter_t {
     let world = *WORLD;
     let parent: ecs_entity_t = parent;
 
     let mut term: ecs_term_t = MaybeUninit::zeroed().assume_init();
     term.id = ecs_make_pair(EcsChildOf, parent);
 
-    let mut iter = ecs_term_iter(world, &mut term);
-    
-    // Convert iter to raw pointer
-    let iter_ptr: *mut ecs_iter_t = &mut iter;
-    iter_ptr
+    let iter = ecs_term_iter(world, &mut term);
+
+    // Box the iterator so the pointer outlives this call
+    Box::into_raw(Box::new(iter))
 }
 
 #[no_mangle]
-pub unsafe fn flecs_term_next(iter: *mut ecs_iter_t) -> bool {
+pub unsafe extern "C" fn flecs_term_next(iter: *mut ecs_iter_t) -> bool {
     ecs_term_next(iter)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_child_entities(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
+pub unsafe extern "C" fn flecs_child_entities(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
     (*iter).entities
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_create() -> *mut ecs_query_desc_t {
+pub unsafe extern "C" fn flecs_entity_add_pair(entity: ecs_entity_t, relationship: ecs_entity_t, target: ecs_entity_t) {
+    let world = *WORLD;
+    ecs_add_id(world, entity, ecs_make_pair(relationship, target));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_remove_pair(entity: ecs_entity_t, relationship: ecs_entity_t, target: ecs_entity_t) {
+    let world = *WORLD;
+    ecs_remove_id(world, entity, ecs_make_pair(relationship, target));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_has_pair(entity: ecs_entity_t, relationship: ecs_entity_t, target: ecs_entity_t) -> bool {
+    let world = *WORLD;
+    ecs_has_id(world, entity, ecs_make_pair(relationship, target))
+}
+
+// Component that holds the data of a pair. This is the relationship unless it
+// has the Tag trait, in which case the target carries the data.
+// Returns 0 if neither element carries data.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_pair_typeid(relationship: ecs_entity_t, target: ecs_entity_t) -> ecs_entity_t {
+    let world = *WORLD;
+    ecs_get_typeid(world, ecs_make_pair(relationship, target))
+}
+
+// Pointer to the pair data, which can be read and written with the same
+// flecs_component_get_member_* / flecs_component_set_member_* accessors.
+// Returns null if the entity isn't alive or the pair doesn't carry data.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_get_pair(
+    entity: ecs_entity_t,
+    relationship: ecs_entity_t,
+    target: ecs_entity_t,
+) -> *mut c_void {
+    let world = *WORLD;
+    let pair = ecs_make_pair(relationship, target);
+    if !data_id_check(world, entity, pair) {
+        return std::ptr::null_mut();
+    }
+    ecs_get_mut_id(world, entity, pair)
+}
+
+// Copies data into the pair, adding it if the entity doesn't have it yet.
+// Returns false without adding the pair if the entity isn't alive or the pair
+// doesn't carry data, in which case flecs_entity_add_pair is the one to use.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_set_pair(
+    entity: ecs_entity_t,
+    relationship: ecs_entity_t,
+    target: ecs_entity_t,
+    data: *const c_void,
+) -> bool {
+    let world = *WORLD;
+    let pair = ecs_make_pair(relationship, target);
+    if !data_id_check(world, entity, pair) {
+        return false;
+    }
+    let type_info = ecs_get_type_info(world, pair);
+    ecs_set_id(world, entity, pair, (*type_info).size as usize, data);
+    true
+}
+
+// Pair data for the index-th target of a relationship, which is how the
+// values of a (Likes, *) pair are walked. Returns null past the last target,
+// and records an error if the pair doesn't carry data.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_get_pair_at(
+    entity: ecs_entity_t,
+    relationship: ecs_entity_t,
+    index: i32,
+) -> *mut c_void {
+    let world = *WORLD;
+    let target = ecs_get_target(world, entity, relationship, index);
+    if target == 0 {
+        return std::ptr::null_mut();
+    }
+    let pair = ecs_make_pair(relationship, target);
+    if !data_id_check(world, entity, pair) {
+        return std::ptr::null_mut();
+    }
+    ecs_get_mut_id(world, entity, pair)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_pair_modified(
+    entity: ecs_entity_t,
+    relationship: ecs_entity_t,
+    target: ecs_entity_t,
+) {
+    let world = *WORLD;
+    ecs_modified_id(world, entity, ecs_make_pair(relationship, target));
+}
+
+// Returns 0 once index is past the last target, so guests can loop until 0
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_get_target(entity: ecs_entity_t, relationship: ecs_entity_t, index: i32) -> ecs_entity_t {
+    let world = *WORLD;
+    ecs_get_target(world, entity, relationship, index)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_get_parent(entity: ecs_entity_t) -> ecs_entity_t {
+    let world = *WORLD;
+    ecs_get_target(world, entity, EcsChildOf, 0)
+}
+
+// Collects all targets of a relationship into a malloc'd array that the
+// caller owns and releases with m_free. The length is written to count.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_get_targets(
+    entity: ecs_entity_t,
+    relationship: ecs_entity_t,
+    count: *mut i32,
+) -> *mut ecs_entity_t {
+    let world = *WORLD;
+    let mut targets: Vec<ecs_entity_t> = Vec::new();
+    loop {
+        let target = ecs_get_target(world, entity, relationship, targets.len() as i32);
+        if target == 0 {
+            break;
+        }
+        targets.push(target);
+    }
+    *count = targets.len() as i32;
+    if targets.is_empty() {
+        return std::ptr::null_mut();
+    }
+
+    let size = targets.len() * std::mem::size_of::<ecs_entity_t>();
+    let targets_ptr = malloc(size) as *mut ecs_entity_t;
+    std::ptr::copy_nonoverlapping(targets.as_ptr(), targets_ptr, targets.len());
+    targets_ptr
+}
+
+// Returns false if the trait code is unknown
+#[no_mangle]
+pub unsafe extern "C" fn flecs_relationship_add_trait(relationship: ecs_entity_t, relationship_trait: u8) -> bool {
+    let world = *WORLD;
+    let Some(relationship_trait) = get_relationship_trait(relationship_trait) else {
+        error_set(ECS_INVALID_PARAMETER, "unknown relationship trait");
+        return false;
+    };
+    ecs_add_id(world, relationship, relationship_trait);
+    true
+}
+
+// Adds a cleanup policy such as (OnDeleteTarget, Delete) to a relationship or
+// component. Returns false if the condition or action code is unknown.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_add_cleanup_policy(entity: ecs_entity_t, condition: u8, action: u8) -> bool {
+    let world = *WORLD;
+    let (Some(condition), Some(action)) = (get_cleanup_condition(condition), get_cleanup_action(action)) else {
+        error_set(ECS_INVALID_PARAMETER, "unknown cleanup condition or action");
+        return false;
+    };
+    ecs_add_id(world, entity, ecs_make_pair(condition, action));
+    true
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_wildcard() -> ecs_entity_t {
+    EcsWildcard
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_any() -> ecs_entity_t {
+    EcsAny
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_is_pair(id: ecs_id_t) -> bool {
+    ecs_id_is_pair(id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_pair_first(pair: ecs_id_t) -> ecs_entity_t {
+    let world = *WORLD;
+    let first = (pair & ECS_COMPONENT_MASK) >> 32;
+    ecs_get_alive(world, first)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_pair_second(pair: ecs_id_t) -> ecs_entity_t {
+    let world = *WORLD;
+    let second = pair & ECS_ENTITY_MASK as u64;
+    ecs_get_alive(world, second)
+}
+
+// Resolves the id a term actually matched, which for a (R, *) term
+// carries the concrete target of the relationship
+#[no_mangle]
+pub unsafe extern "C" fn flecs_iter_pair_target(iter: *mut ecs_iter_t, term_index: i32) -> ecs_entity_t {
+    let id = ecs_field_id(iter, term_index);
+    if !ecs_id_is_pair(id) {
+        return 0;
+    }
+    flecs_pair_second(id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_query_create() -> *mut ecs_query_desc_t {
     let desc: ecs_query_desc_t = MaybeUninit::zeroed().assume_init();
     Box::into_raw(Box::new(desc))
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_with(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+pub unsafe extern "C" fn flecs_query_with(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
     // Slice from raw parts
-    let ids = std::slice::from_raw_parts(ids as *mut u64, components_count as usize);
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
 
     let world = *WORLD;
 
@@ -353,14 +906,14 @@ pub unsafe fn flecs_query_with(query_desc: *mut ecs_query_desc_t, filter_index:
         (*query_desc).filter.terms[filter_index as usize + index] = term;
         new_filter_index = index as u8;
     }
-    new_filter_index as u8
+    new_filter_index
 }
 
 
 #[no_mangle]
-pub unsafe fn flecs_query_without(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+pub unsafe extern "C" fn flecs_query_without(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
     // Slice from raw parts
-    let ids = std::slice::from_raw_parts(ids as *mut u64, components_count as usize);
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
 
     let world = *WORLD;
 
@@ -377,9 +930,9 @@ pub unsafe fn flecs_query_without(query_desc: *mut ecs_query_desc_t, filter_inde
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_with_or(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+pub unsafe extern "C" fn flecs_query_with_or(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
     // Slice from raw parts
-    let ids = std::slice::from_raw_parts(ids as *mut u64, components_count as usize);
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
 
     let world = *WORLD;
 
@@ -395,37 +948,161 @@ pub unsafe fn flecs_query_with_or(query_desc: *mut ecs_query_desc_t, filter_inde
     new_filter_index
 }
 
+// Adds terms that match the singletons of the ids instead of the matched
+// entity, like Game($) in the query DSL. Returns the next free term index,
+// also when there are no ids.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_query_with_singleton(query_desc: *mut ecs_query_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+    flecs_filter_with_singleton(&mut (*query_desc).filter, filter_index, ids, components_count)
+}
+
 #[no_mangle]
-pub unsafe fn flecs_query_build(desc: *mut ecs_query_desc_t) -> *mut ecs_query_t {
+pub unsafe extern "C" fn flecs_query_build(desc: *mut ecs_query_desc_t) -> *mut ecs_query_t {
     let world = *WORLD;
+    if desc.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "query description is null");
+        return std::ptr::null_mut();
+    }
     let query: *mut ecs_query_t = ecs_query_init(world, desc);
     query
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_next(iter: *mut ecs_iter_t) -> bool {
+pub unsafe extern "C" fn flecs_query_next(iter: *mut ecs_iter_t) -> bool {
     ecs_query_next(iter)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_iter(query: *mut ecs_query_t) -> *mut ecs_iter_t {
+pub unsafe extern "C" fn flecs_query_iter(query: *mut ecs_query_t) -> *mut ecs_iter_t {
     let world = *WORLD;
+    if query.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "query is null");
+        return std::ptr::null_mut();
+    }
     let it = ecs_query_iter(world, query);
-    let it_ptr = Box::into_raw(Box::new(it));
-    it_ptr
+    Box::into_raw(Box::new(it))
+}
+
+// Releases the resources of an iterator that is dropped before its next
+// function returned false
+#[no_mangle]
+pub unsafe extern "C" fn flecs_iter_fini(iter: *mut ecs_iter_t) {
+    ecs_iter_fini(iter);
+}
+
+// Frees an iterator returned by flecs_query_iter, flecs_filter_iter or
+// flecs_entity_children
+#[no_mangle]
+pub unsafe extern "C" fn flecs_iter_destroy(iter: *mut ecs_iter_t) {
+    drop(Box::from_raw(iter));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_query_destroy(query: *mut ecs_query_t) {
+    ecs_query_fini(query);
 }
 
 #[no_mangle]
-pub unsafe fn flecs_iter_count(iter: *mut ecs_iter_t) -> i32 {
+pub unsafe extern "C" fn flecs_iter_count(iter: *mut ecs_iter_t) -> i32 {
     (*iter).count
 }
 
+// Advances a query iterator by one matched table without populating the
+// iterator fields. Columns of the table are then read directly through the
+// flecs_table_* exports. Call flecs_query_populate if the entity ids or
+// field pointers of the current table are also needed.
+// Change detection only sees writes to the columns of tables that were
+// populated, so call flecs_query_populate before writing to a table if other
+// queries check for changes.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_query_next_table(iter: *mut ecs_iter_t) -> bool {
+    ecs_query_next_table(iter)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_query_populate(iter: *mut ecs_iter_t, when_changed: bool) -> i32 {
+    ecs_query_populate(iter, when_changed)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_iter_table(iter: *mut ecs_iter_t) -> *mut ecs_table_t {
+    (*iter).table
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_table_count(table: *mut ecs_table_t) -> i32 {
+    ecs_table_count(table)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_table_column_count(table: *mut ecs_table_t) -> i32 {
+    ecs_table_column_count(table)
+}
+
+// Index of the column storing the component, or -1 for tags and ids the
+// table doesn't have
+#[no_mangle]
+pub unsafe extern "C" fn flecs_table_get_column_index(table: *mut ecs_table_t, id: ecs_id_t) -> i32 {
+    let world = *WORLD;
+    ecs_table_get_column_index(world, table, id)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_table_get_column(table: *mut ecs_table_t, column_index: i32, offset: i32) -> *mut c_void {
+    ecs_table_get_column(table, column_index, offset)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_table_get_column_size(table: *mut ecs_table_t, column_index: i32) -> usize {
+    ecs_table_get_column_size(table, column_index)
+}
+
+// View of one component column of a table. Element i lives at
+// ptr + i * stride, for i in 0..count.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct flecs_column_t {
+    pub ptr: *mut c_void,
+    pub stride: usize,
+    pub count: i32,
+}
+
+// Fills one column view per id for the table, so that a guest can map every
+// field of the current table as a typed array with a single call. Ids
+// without a column in the table (tags, or components the table doesn't
+// have) produce a null pointer with a stride of 0. Returns the row count.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_table_columns(
+    table: *mut ecs_table_t,
+    ids: *const ecs_id_t,
+    ids_count: i32,
+    columns: *mut flecs_column_t,
+) -> i32 {
+    let world = *WORLD;
+    let count = ecs_table_count(table);
+    let ids = std::slice::from_raw_parts(ids, ids_count as usize);
+    let columns = std::slice::from_raw_parts_mut(columns, ids_count as usize);
+    for (index, id) in ids.iter().enumerate() {
+        let column_index = ecs_table_get_column_index(world, table, *id);
+        columns[index] = if column_index == -1 {
+            flecs_column_t { ptr: std::ptr::null_mut(), stride: 0, count }
+        } else {
+            flecs_column_t {
+                ptr: ecs_table_get_column(table, column_index, 0),
+                stride: ecs_table_get_column_size(table, column_index),
+                count,
+            }
+        };
+    }
+    count
+}
+
 // This is for the guest to get the pointers to the components based on the index
 // of the component when the query was created
 // That's why there is an array of arrays. The first array is the first component type as an array of pointers
 
 #[no_mangle]
-pub unsafe fn flecs_iter_ptrs(
+pub unsafe extern "C" fn flecs_iter_ptrs(
     iter: *mut ecs_iter_t,
     component_query_index: u32,
 ) -> *mut *mut c_void {
@@ -433,18 +1110,40 @@ pub unsafe fn flecs_iter_ptrs(
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_iter_component(
+pub unsafe extern "C" fn flecs_query_iter_component(
     component_array_ptr: *mut u8,
     component_index: u32,
     count: u32,
     component_id: ecs_entity_t,
 ) -> *const u8 {
     let world = *WORLD;
+    iter_component(world, component_array_ptr, component_index, count, component_id)
+}
 
+// Pointer to the component_index-th of count values in a component array.
+// Returns null if the id doesn't carry data or the index is out of range.
+unsafe fn iter_component(
+    world: *const ecs_world_t,
+    component_array_ptr: *mut u8,
+    component_index: u32,
+    count: u32,
+    component_id: ecs_entity_t,
+) -> *const u8 {
+    if component_array_ptr.is_null() || component_index >= count {
+        error_set(ECS_INVALID_PARAMETER, "component array is null or the index is out of range");
+        return std::ptr::null();
+    }
     // TODO: Have this size value already on the host side in stead of
     // Looking up ecs_get_type_info every time
-    let component: ecs_entity_t = component_id;
-    let type_info = ecs_get_type_info(world, component);
+    let type_info = if ecs_id_is_valid(world, component_id) {
+        ecs_get_type_info(world, component_id)
+    } else {
+        std::ptr::null()
+    };
+    if type_info.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "id is not a component that carries data");
+        return std::ptr::null();
+    }
     let component_size = (*type_info).size as usize;
 
     let ptrs_slice =
@@ -453,73 +1152,136 @@ pub unsafe fn flecs_query_iter_component(
     ptr as *const u8
 }
 
+// flecs reads past the fields of the iterator for a term index out of range,
+// so the field getters check it first. Records the error if it is.
+unsafe fn field_check(iter: *const ecs_iter_t, term_index: i32) -> bool {
+    if iter.is_null() || term_index < 1 || term_index > (*iter).field_count {
+        error_set(ECS_INVALID_PARAMETER, "iterator is null or the term index is out of range");
+        return false;
+    }
+    true
+}
+
+// Entity at index of the iterated entities, or 0 if the index is out of range
+unsafe fn iter_entity(iter: *const ecs_iter_t, count: u32, index: u32) -> ecs_entity_t {
+    if iter.is_null() || (*iter).entities.is_null() || index >= count || count > (*iter).count as u32 {
+        error_set(ECS_INVALID_PARAMETER, "iterator has no entities or the index is out of range");
+        return 0;
+    }
+    let entities_slice = std::slice::from_raw_parts((*iter).entities, count as usize);
+    entities_slice[index as usize]
+}
+
+// Resolves the pointer to the index-th value of a field. Fields that are not
+// matched on the iterated entities themselves (inherited from a prefab
+// through IsA, or matched with up traversal) are shared and only have a
+// single value. Optional fields that were not matched have no data at all.
+unsafe fn field_ptr(iter: *mut ecs_iter_t, term_index: i32, count: u32, index: u32) -> *const c_void {
+    if !field_check(iter, term_index) || !ecs_field_is_set(iter, term_index) {
+        return std::ptr::null();
+    }
+    let size = ecs_field_size(iter, term_index);
+    let field = ecs_field_w_size(iter, size, term_index);
+    if field.is_null() {
+        return std::ptr::null();
+    }
+    if !ecs_field_is_self(iter, term_index) {
+        return field;
+    }
+    if index >= count || count > (*iter).count as u32 {
+        error_set(ECS_INVALID_PARAMETER, "index is out of range");
+        return std::ptr::null();
+    }
+
+    // Create pointer for an offset in field which is an array of component data
+    let ptrs_slice = std::slice::from_raw_parts(field as *const u8, count as usize * size);
+    let ptr = &ptrs_slice[index as usize * size];
+    ptr as *const u8 as *const c_void
+}
+
+// Array of count component pointers, allocated with malloc so that any
+// language can release it with m_free
+unsafe fn field_ptr_list(iter: *mut ecs_iter_t, term_index: i32, count: u32) -> *mut *const c_void {
+    if count == 0 || !field_check(iter, term_index) {
+        return std::ptr::null_mut();
+    }
+    let component_ptrs = malloc(count as usize * std::mem::size_of::<*const c_void>()) as *mut *const c_void;
+    for i in 0..count {
+        *component_ptrs.add(i as usize) = field_ptr(iter, term_index, count, i);
+    }
+    component_ptrs
+}
+
+// False when the field is shared, i.e. matched on another entity such as a
+// prefab. A shared field has one value for all entities of the iterated table.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_field_is_self(iter: *mut ecs_iter_t, term_index: i32) -> bool {
+    ecs_field_is_self(iter, term_index)
+}
+
+// False when an optional field didn't match, in which case it has no data
+#[no_mangle]
+pub unsafe extern "C" fn flecs_field_is_set(iter: *mut ecs_iter_t, term_index: i32) -> bool {
+    ecs_field_is_set(iter, term_index)
+}
+
+// Entity the field was matched on, or 0 when matched on the iterated entities
+#[no_mangle]
+pub unsafe extern "C" fn flecs_field_src(iter: *mut ecs_iter_t, term_index: i32) -> ecs_entity_t {
+    ecs_field_src(iter, term_index)
+}
+
+// Id the field was matched with, which resolves wildcards to the actual pair
+#[no_mangle]
+pub unsafe extern "C" fn flecs_field_id(iter: *mut ecs_iter_t, term_index: i32) -> ecs_id_t {
+    ecs_field_id(iter, term_index)
+}
+
 #[no_mangle]
-pub unsafe fn flecs_query_field(
+pub unsafe extern "C" fn flecs_query_field(
     iter: *mut ecs_iter_t,
     term_index: i32,
     count: u32,
     index: u32,
 ) -> *const c_void {
-    let size = ecs_field_size(iter, term_index);
-    let field = ecs_field_w_size(iter, size, term_index);
-
-    // Create pointer for an offset in field which is an array of component data
-    let ptrs_slice = std::slice::from_raw_parts(field, count as usize * size);
-    let ptr = &ptrs_slice[index as usize * size];
-    ptr as *const c_void
+    field_ptr(iter, term_index, count, index)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_field_size(
+pub unsafe extern "C" fn flecs_query_field_size(
     iter: *mut ecs_iter_t,
     term_index: i32,
 ) -> usize {
+    if !field_check(iter, term_index) {
+        return 0;
+    }
     ecs_field_size(iter, term_index)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_field_list(
+pub unsafe extern "C" fn flecs_query_field_list(
     iter: *mut ecs_iter_t,
     term_index: i32,
     count: u32
-) -> *mut [*const c_void] {
-    let size = ecs_field_size(iter, term_index);
-    let field = ecs_field_w_size(iter, size, term_index);
-
-    let ptrs_slice = std::slice::from_raw_parts(field, count as usize * size);
-    let mut component_ptrs: Vec<*const c_void> = Vec::new();
-
-    for i in 0..count {
-        let ptr = &ptrs_slice[i as usize * size];
-        component_ptrs.push(ptr as *const c_void);
-    }
-
-    let boxed_slice = component_ptrs.into_boxed_slice();
-    let raw_ptr = Box::into_raw(boxed_slice);
-
-    raw_ptr
+) -> *mut *const c_void {
+    field_ptr_list(iter, term_index, count)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_entity(iter: *mut ecs_iter_t, count: u32, index: u32) -> ecs_entity_t {
-    let world = *WORLD;
-    let entities = (*iter).entities;
-    let entities_slice = std::slice::from_raw_parts(entities, count as usize);
-    let entity = entities_slice[index as usize];
-    entity
+pub unsafe extern "C" fn flecs_query_entity(iter: *mut ecs_iter_t, count: u32, index: u32) -> ecs_entity_t {
+    iter_entity(iter, count, index)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_query_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
+pub unsafe extern "C" fn flecs_query_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
     let world = *WORLD;
-    let entities = (*iter).entities;
-    entities
+    (*iter).entities
 }
 
 // TODO: Take another look at whether this is nessecary, because we don't want to copy data
 /*
 #[no_mangle]
-pub unsafe fn flecs_query_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
+pub unsafe extern "C" fn flecs_query_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
     let world = *WORLD;
     let entities_ptr = (*iter).entities;
 
@@ -535,15 +1297,15 @@ pub unsafe fn flecs_query_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_
 */
 
 #[no_mangle]
-pub unsafe fn flecs_filter_create() -> *mut ecs_filter_desc_t {
+pub unsafe extern "C" fn flecs_filter_create() -> *mut ecs_filter_desc_t {
     let desc: ecs_filter_desc_t = MaybeUninit::zeroed().assume_init();
     Box::into_raw(Box::new(desc))
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_with(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+pub unsafe extern "C" fn flecs_filter_with(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
     // Slice from raw parts
-    let ids = std::slice::from_raw_parts(ids as *mut u64, components_count as usize);
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
 
     let world = *WORLD;
 
@@ -565,9 +1327,9 @@ pub unsafe fn flecs_filter_with(filter_desc: *mut ecs_filter_desc_t, filter_inde
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_without(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+pub unsafe extern "C" fn flecs_filter_without(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
     // Slice from raw parts
-    let ids = std::slice::from_raw_parts(ids as *mut u64, components_count as usize);
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
 
     let world = *WORLD;
 
@@ -584,9 +1346,9 @@ pub unsafe fn flecs_filter_without(filter_desc: *mut ecs_filter_desc_t, filter_i
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_with_or(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+pub unsafe extern "C" fn flecs_filter_with_or(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
     // Slice from raw parts
-    let ids = std::slice::from_raw_parts(ids as *mut u64, components_count as usize);
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
 
     let world = *WORLD;
 
@@ -602,235 +1364,224 @@ pub unsafe fn flecs_filter_with_or(filter_desc: *mut ecs_filter_desc_t, filter_i
     new_filter_index as u8
 }
 
+// Adds terms that match the singletons of the ids instead of the matched
+// entity, like Game($) in the query DSL. Returns the next free term index,
+// also when there are no ids.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_filter_with_singleton(filter_desc: *mut ecs_filter_desc_t, filter_index: u8, ids: *mut ecs_entity_t, components_count: i32) -> u8 {
+    let ids = std::slice::from_raw_parts(ids, components_count as usize);
+    for (index, id) in ids.iter().enumerate() {
+        let mut term: ecs_term_t = MaybeUninit::zeroed().assume_init();
+        term.id = *id;
+        term.oper = ecs_oper_kind_t_EcsAnd;
+        // A $ source by itself resolves to the term's own id
+        term.src.id = EcsVariable;
+        term.src.flags = EcsIsVariable;
+        (*filter_desc).terms[filter_index as usize + index] = term;
+    }
+    filter_index + ids.len() as u8
+}
+
 #[no_mangle]
-pub unsafe fn flecs_filter_build(desc: *mut ecs_filter_desc_t) -> *mut ecs_filter_t {
+pub unsafe extern "C" fn flecs_filter_build(desc: *mut ecs_filter_desc_t) -> *mut ecs_filter_t {
     let world = *WORLD;
+    if desc.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "filter description is null");
+        return std::ptr::null_mut();
+    }
     let filter: *mut ecs_filter_t = ecs_filter_init(world, desc);
     filter
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_next(iter: *mut ecs_iter_t) -> bool {
+pub unsafe extern "C" fn flecs_filter_next(iter: *mut ecs_iter_t) -> bool {
     ecs_filter_next(iter)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_iter_component(
+pub unsafe extern "C" fn flecs_filter_iter_component(
     component_array_ptr: *mut u8,
     component_index: u32,
     count: u32,
     component_id: ecs_entity_t,
 ) -> *const u8 {
     let world = *WORLD;
-
-    // TODO: Have this size value already on the host side in stead of
-    // Looking up ecs_get_type_info every time
-    let component: ecs_entity_t = component_id;
-    let type_info = ecs_get_type_info(world, component);
-    let component_size = (*type_info).size as usize;
-
-    let ptrs_slice =
-        std::slice::from_raw_parts(component_array_ptr, count as usize * component_size);
-    let ptr = &ptrs_slice[(component_index as usize) * component_size];
-    ptr as *const u8
+    iter_component(world, component_array_ptr, component_index, count, component_id)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_field(
+pub unsafe extern "C" fn flecs_filter_field(
     iter: *mut ecs_iter_t,
     term_index: i32,
     count: u32,
     index: u32,
 ) -> *const c_void {
-    let size = ecs_field_size(iter, term_index);
-    let field = ecs_field_w_size(iter, size, term_index);
-
-    // Create pointer for an offset in field which is an array of component data
-    let ptrs_slice = std::slice::from_raw_parts(field, count as usize * size);
-    let ptr = &ptrs_slice[index as usize * size];
-    ptr as *const c_void
+    field_ptr(iter, term_index, count, index)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_field_size(
+pub unsafe extern "C" fn flecs_filter_field_size(
     iter: *mut ecs_iter_t,
     term_index: i32,
 ) -> usize {
+    if !field_check(iter, term_index) {
+        return 0;
+    }
     ecs_field_size(iter, term_index)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_field_list(
+pub unsafe extern "C" fn flecs_filter_field_list(
     iter: *mut ecs_iter_t,
     term_index: i32,
     count: u32
-) -> *mut [*const c_void] {
-    let size = ecs_field_size(iter, term_index);
-    let field = ecs_field_w_size(iter, size, term_index);
-    // Create pointer for an offset in field which is an array of component data
-    let ptrs_slice = std::slice::from_raw_parts(field, count as usize * size);
-    // Create a new vec and add new pointers to the component
-    // to the vector
-    let mut component_ptrs: Vec<*const c_void> = Vec::new();
-    for i in 0..count {
-        let ptr = &ptrs_slice[i as usize * size];
-        component_ptrs.push(ptr as *const c_void);
-    }
-
-    let boxed_slice = component_ptrs.into_boxed_slice();
-    let raw_ptr = Box::into_raw(boxed_slice);
-
-    raw_ptr
+) -> *mut *const c_void {
+    field_ptr_list(iter, term_index, count)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_entity(iter: *mut ecs_iter_t, count: u32, index: u32) -> ecs_entity_t {
-    let world = *WORLD;
-    let entities = (*iter).entities;
-    let entities_slice = std::slice::from_raw_parts(entities, count as usize);
-    let entity = entities_slice[index as usize];
-    entity
+pub unsafe extern "C" fn flecs_filter_entity(iter: *mut ecs_iter_t, count: u32, index: u32) -> ecs_entity_t {
+    iter_entity(iter, count, index)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_filter_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
+pub unsafe extern "C" fn flecs_filter_entity_list(iter: *mut ecs_iter_t) -> *mut ecs_entity_t {
     let world = *WORLD;
-    let entities = (*iter).entities;
-    entities
+    (*iter).entities
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_u8(component_ptr: *mut c_void, offset: u32, value: u8) {
+pub unsafe extern "C" fn flecs_component_set_member_u8(component_ptr: *mut c_void, offset: u32, value: u8) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u8;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_u8(component_ptr: *mut c_void, offset: u32) -> u8 {
+pub unsafe extern "C" fn flecs_component_get_member_u8(component_ptr: *mut c_void, offset: u32) -> u8 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u8;
     let member_value: u8 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_u16(component_ptr: *mut c_void, offset: u32, value: u16) {
+pub unsafe extern "C" fn flecs_component_set_member_u16(component_ptr: *mut c_void, offset: u32, value: u16) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u16;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_u16(component_ptr: *mut c_void, offset: u32) -> u16 {
+pub unsafe extern "C" fn flecs_component_get_member_u16(component_ptr: *mut c_void, offset: u32) -> u16 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u16;
     let member_value: u16 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_u32(component_ptr: *mut c_void, offset: u32, value: u32) {
+pub unsafe extern "C" fn flecs_component_set_member_u32(component_ptr: *mut c_void, offset: u32, value: u32) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u32;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_u32(component_ptr: *mut c_void, offset: u32) -> u32 {
+pub unsafe extern "C" fn flecs_component_get_member_u32(component_ptr: *mut c_void, offset: u32) -> u32 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u32;
     let member_value: u32 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_u64(component_ptr: *mut c_void, offset: u32, value: u64) {
+pub unsafe extern "C" fn flecs_component_set_member_u64(component_ptr: *mut c_void, offset: u32, value: u64) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u64;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_u64(component_ptr: *mut c_void, offset: u32) -> u64 {
+pub unsafe extern "C" fn flecs_component_get_member_u64(component_ptr: *mut c_void, offset: u32) -> u64 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut u64;
     let member_value: u64 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_i8(component_ptr: *mut c_void, offset: u32, value: i8) {
+pub unsafe extern "C" fn flecs_component_set_member_i8(component_ptr: *mut c_void, offset: u32, value: i8) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i8;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_i8(component_ptr: *mut c_void, offset: u32) -> i8 {
+pub unsafe extern "C" fn flecs_component_get_member_i8(component_ptr: *mut c_void, offset: u32) -> i8 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i8;
     let member_value: i8 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_i16(component_ptr: *mut c_void, offset: u32, value: i16) {
+pub unsafe extern "C" fn flecs_component_set_member_i16(component_ptr: *mut c_void, offset: u32, value: i16) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i16;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_i16(component_ptr: *mut c_void, offset: u32) -> i16 {
+pub unsafe extern "C" fn flecs_component_get_member_i16(component_ptr: *mut c_void, offset: u32) -> i16 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i16;
     let member_value: i16 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_i32(component_ptr: *mut c_void, offset: u32, value: i32) {
+pub unsafe extern "C" fn flecs_component_set_member_i32(component_ptr: *mut c_void, offset: u32, value: i32) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i32;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_i32(component_ptr: *mut c_void, offset: u32) -> i32 {
+pub unsafe extern "C" fn flecs_component_get_member_i32(component_ptr: *mut c_void, offset: u32) -> i32 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i32;
     let member_value: i32 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_i64(component_ptr: *mut c_void, offset: u32, value: i64) {
+pub unsafe extern "C" fn flecs_component_set_member_i64(component_ptr: *mut c_void, offset: u32, value: i64) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i64;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_i64(component_ptr: *mut c_void, offset: u32) -> i64 {
+pub unsafe extern "C" fn flecs_component_get_member_i64(component_ptr: *mut c_void, offset: u32) -> i64 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut i64;
     let member_value: i64 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_f32(component_ptr: *mut c_void, offset: u32, value: f32) {
+pub unsafe extern "C" fn flecs_component_set_member_f32(component_ptr: *mut c_void, offset: u32, value: f32) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut f32;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_f32(component_ptr: *mut c_void, offset: u32) -> f32 {
+pub unsafe extern "C" fn flecs_component_get_member_f32(component_ptr: *mut c_void, offset: u32) -> f32 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut f32;
     let member_value: f32 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_f64(component_ptr: *mut c_void, offset: u32, value: f64) {
+pub unsafe extern "C" fn flecs_component_set_member_f64(component_ptr: *mut c_void, offset: u32, value: f64) {
     let member_ptr = component_ptr.offset(offset as isize) as *mut f64;
     *member_ptr = value;
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_f64(component_ptr: *mut c_void, offset: u32) -> f64 {
+pub unsafe extern "C" fn flecs_component_get_member_f64(component_ptr: *mut c_void, offset: u32) -> f64 {
     let member_ptr = component_ptr.offset(offset as isize) as *mut f64;
     let member_value: f64 = *member_ptr;
     member_value
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_bool(
+pub unsafe extern "C" fn flecs_component_set_member_bool(
     component_ptr: *mut c_void,
     offset: u32,
     value: bool,
@@ -840,24 +1591,111 @@ pub unsafe fn flecs_component_set_member_bool(
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_bool(component_ptr: *mut c_void, offset: u32) -> bool {
+pub unsafe extern "C" fn flecs_component_get_member_bool(component_ptr: *mut c_void, offset: u32) -> bool {
     let member_ptr = component_ptr.offset(offset as isize) as *mut bool;
     let member_value: bool = *member_ptr;
     member_value
 }
 
+// Owned member data goes through the flecs OS API, like the memory flecs
+// allocates itself
+unsafe fn os_malloc(size: usize) -> *mut c_void {
+    ecs_os_api.malloc_.unwrap()(size as ecs_size_t)
+}
+
+unsafe fn os_free(ptr: *mut c_void) {
+    ecs_os_api.free_.unwrap()(ptr)
+}
+
+unsafe fn os_strdup(value: *const c_char) -> *mut c_char {
+    ecs_os_api.strdup_.unwrap()(value)
+}
+
+// Moves a string flecs allocated to malloc'd memory, so that m_free can
+// release it whatever allocator flecs uses
+unsafe fn os_string_to_malloc(value: *mut c_char) -> *mut c_char {
+    if value.is_null() {
+        return value;
+    }
+    let size = std::ffi::CStr::from_ptr(value).to_bytes_with_nul().len();
+    let copy = malloc(size) as *mut c_char;
+    std::ptr::copy_nonoverlapping(value, copy, size);
+    os_free(value as *mut c_void);
+    copy
+}
+
+// Copies values to a malloc'd array the caller releases with m_free. Returns
+// null when there are none, instead of a malloc(0) that may be null or not.
+unsafe fn slice_to_malloc<T: Copy>(values: &[T]) -> *mut T {
+    if values.is_empty() {
+        return std::ptr::null_mut();
+    }
+    let values_ptr = malloc(std::mem::size_of_val(values)) as *mut T;
+    std::ptr::copy_nonoverlapping(values.as_ptr(), values_ptr, values.len());
+    values_ptr
+}
+
+// JSON string literal of value
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// JSON number of value. Infinity and NaN have no JSON spelling and are null.
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+// Arrays start with their length, stored in the element type
+unsafe fn array_dup(value: *const c_void, length: usize) -> *mut c_void {
+    if value.is_null() {
+        return std::ptr::null_mut();
+    }
+    let size = (length + 1) * 4;
+    let copy = os_malloc(size);
+    std::ptr::copy_nonoverlapping(value as *const u8, copy as *mut u8, size);
+    copy
+}
+
+// String, array and vector members are owned by the component: the setters
+// copy the value, so callers keep ownership of theirs, and the default hooks
+// free, copy and move them with the component. The setters return false when
+// the component has no member of their kind at offset, since they free the
+// value that is there.
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_string(
+pub unsafe extern "C" fn flecs_component_set_member_string(
+    component: ecs_entity_t,
     component_ptr: *mut c_void,
     offset: u32,
-    value: *mut c_char,
-) {
+    value: *const c_char,
+) -> bool {
+    if !matches!(owned_member(component, offset), Some(OwnedMember::String(_))) {
+        error_set(ECS_INVALID_PARAMETER, "member is not a string");
+        return false;
+    }
     let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut c_char;
-    *member_ptr = value;
+    let previous = *member_ptr;
+    *member_ptr = os_strdup(value);
+    os_free(previous as *mut c_void);
+    true
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_string(
+pub unsafe extern "C" fn flecs_component_get_member_string(
     component_ptr: *mut c_void,
     offset: u32,
 ) -> *mut c_char {
@@ -866,189 +1704,2359 @@ pub unsafe fn flecs_component_get_member_string(
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_u32array(
+pub unsafe extern "C" fn flecs_component_set_member_u32array(
+    component: ecs_entity_t,
     component_ptr: *mut c_void,
     offset: u32,
-    value: *mut u32,
-) {
+    value: *const u32,
+) -> bool {
+    if !matches!(owned_member(component, offset), Some(OwnedMember::U32Array(_))) {
+        error_set(ECS_INVALID_PARAMETER, "member is not a u32 array");
+        return false;
+    }
     let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut u32;
-    *member_ptr = value;
+    let previous = *member_ptr;
+    let length = if value.is_null() { 0 } else { *value as usize };
+    *member_ptr = array_dup(value as *const c_void, length) as *mut u32;
+    os_free(previous as *mut c_void);
+    true
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_u32array(
+pub unsafe extern "C" fn flecs_component_get_member_u32array(
     component_ptr: *mut c_void,
     offset: u32,
 ) -> *mut u32 {
     let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut u32;
-    *member_ptr as *mut u32
+    *member_ptr
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_set_member_f32array(
+pub unsafe extern "C" fn flecs_component_set_member_f32array(
+    component: ecs_entity_t,
     component_ptr: *mut c_void,
     offset: u32,
-    value: *mut f32,
-) {
+    value: *const f32,
+) -> bool {
+    if !matches!(owned_member(component, offset), Some(OwnedMember::F32Array(_))) {
+        error_set(ECS_INVALID_PARAMETER, "member is not a f32 array");
+        return false;
+    }
     let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut f32;
-    *member_ptr = value;
+    let previous = *member_ptr;
+    let length = if value.is_null() { 0 } else { *value as usize };
+    *member_ptr = array_dup(value as *const c_void, length) as *mut f32;
+    os_free(previous as *mut c_void);
+    true
 }
 
 #[no_mangle]
-pub unsafe fn flecs_component_get_member_f32array(
+pub unsafe extern "C" fn flecs_component_get_member_f32array(
     component_ptr: *mut c_void,
     offset: u32,
 ) -> *mut f32 {
     let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut f32;
-    *member_ptr as *mut f32
+    *member_ptr
 }
 
+// Replaces the elements of a vector member with a copy of count values of
+// element_size bytes. Returns false when the component has no vector member
+// at offset, or its elements aren't element_size bytes.
 #[no_mangle]
-pub unsafe fn flecs_progress(delta_time: f32) -> bool {
-    let world = *WORLD;
-    ecs_progress(world, delta_time)
+pub unsafe extern "C" fn flecs_component_set_member_vector(
+    component: ecs_entity_t,
+    component_ptr: *mut c_void,
+    offset: u32,
+    values: *const c_void,
+    count: i32,
+    element_size: i32,
+) -> bool {
+    match owned_member(component, offset) {
+        Some(OwnedMember::Vector { element_size: size, .. }) if *size == element_size => {}
+        Some(OwnedMember::StringVector(_)) => {
+            error_set(ECS_INVALID_PARAMETER, "string vectors are set with flecs_component_set_member_string_vector");
+            return false;
+        }
+        _ => {
+            error_set(ECS_INVALID_PARAMETER, "member is not a vector of elements of element_size bytes");
+            return false;
+        }
+    }
+    let byte_count = match count.checked_mul(element_size) {
+        Some(byte_count) if count >= 0 && element_size > 0 => byte_count,
+        _ => {
+            error_set(ECS_INVALID_PARAMETER, "count * element_size is negative or overflows");
+            return false;
+        }
+    };
+    let vector = (component_ptr as *mut u8).add(offset as usize) as *mut ecs_vec_t;
+    ecs_vec_set_count(std::ptr::null_mut(), vector, element_size, count);
+    if count > 0 {
+        std::ptr::copy_nonoverlapping(values as *const u8, (*vector).array as *mut u8, byte_count as usize);
+    }
+    true
 }
 
+// Replaces the elements of a string vector member with copies of the strings.
+// Returns false when the component has no string vector member at offset.
 #[no_mangle]
-pub unsafe fn flecs_make_pair(relation: ecs_entity_t, object: ecs_entity_t) -> ecs_entity_t {
-    let relation: ecs_entity_t = relation;
-    let object: ecs_entity_t = object;
-    ecs_make_pair(relation, object)
+pub unsafe extern "C" fn flecs_component_set_member_string_vector(
+    component: ecs_entity_t,
+    component_ptr: *mut c_void,
+    offset: u32,
+    values: *const *const c_char,
+    count: i32,
+) -> bool {
+    if !matches!(owned_member(component, offset), Some(OwnedMember::StringVector(_))) || count < 0 {
+        error_set(ECS_INVALID_PARAMETER, "member is not a string vector");
+        return false;
+    }
+    let vector = (component_ptr as *mut u8).add(offset as usize) as *mut ecs_vec_t;
+    string_vector_clear(vector);
+    let element_size = std::mem::size_of::<*mut c_char>() as i32;
+    ecs_vec_set_count(std::ptr::null_mut(), vector, element_size, count);
+    for index in 0..count as usize {
+        *((*vector).array as *mut *mut c_char).add(index) = os_strdup(*values.add(index));
+    }
+    true
 }
 
+// Returns the elements of a vector member and writes their count
 #[no_mangle]
-pub unsafe fn flecs_filter_children_init(id: ecs_entity_t) -> *mut ecs_filter_t {
-    let world = *WORLD;
-    let mut desc: ecs_filter_desc_t = MaybeUninit::zeroed().assume_init();
-    desc.terms[0].id = ecs_make_pair(EcsChildOf, id);
-    desc.terms[1].id = EcsPrefab;
-    desc.terms[1].oper = ecs_oper_kind_t_EcsOptional;
-    ecs_filter_init(world, &desc)
+pub unsafe extern "C" fn flecs_component_get_member_vector(
+    component_ptr: *mut c_void,
+    offset: u32,
+    count: *mut i32,
+) -> *mut c_void {
+    let vector = (component_ptr as *mut u8).add(offset as usize) as *const ecs_vec_t;
+    *count = ecs_vec_count(vector);
+    ecs_vec_first(vector)
 }
 
-#[no_mangle]
-pub unsafe fn flecs_filter_iter(filter: *mut ecs_filter_t) -> *mut ecs_iter_t {
-    let world = *WORLD;
-    let it = ecs_filter_iter(world, filter);
-    let it_ptr = Box::into_raw(Box::new(it));
-    it_ptr
+// Frees the strings of a string vector, keeping its storage
+unsafe fn string_vector_clear(vector: *mut ecs_vec_t) {
+    let strings = (*vector).array as *mut *mut c_char;
+    for index in 0..ecs_vec_count(vector) as usize {
+        os_free(*strings.add(index) as *mut c_void);
+    }
+    (*vector).count = 0;
 }
 
-#[no_mangle]
-pub unsafe fn flecs_iter_entities(iter: *mut ecs_iter_t) -> &'static [ecs_entity_t] {
-    let entities = (*iter).entities;
-    if (*iter).count > 0 {
-        let entities_slice = std::slice::from_raw_parts(entities, (*iter).count as usize);
-        entities_slice
-    } else {
-        &[]
+// Lifecycle callbacks for component data. They get the size of the component
+// and the ctx pointer that was registered with the hooks.
+pub type flecs_xtor_callback_t = unsafe extern "C" fn(ptr: *mut c_void, count: i32, size: i32, ctx: *mut c_void);
+pub type flecs_copy_callback_t =
+    unsafe extern "C" fn(dst_ptr: *mut c_void, src_ptr: *const c_void, count: i32, size: i32, ctx: *mut c_void);
+pub type flecs_move_callback_t =
+    unsafe extern "C" fn(dst_ptr: *mut c_void, src_ptr: *mut c_void, count: i32, size: i32, ctx: *mut c_void);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct flecs_type_hooks_t {
+    pub ctor: Option<flecs_xtor_callback_t>,
+    pub dtor: Option<flecs_xtor_callback_t>,
+    pub copy: Option<flecs_copy_callback_t>,
+    pub move_: Option<flecs_move_callback_t>,
+    pub on_add: Option<flecs_iter_callback_t>,
+    pub on_set: Option<flecs_iter_callback_t>,
+    pub on_remove: Option<flecs_iter_callback_t>,
+    pub ctx: *mut c_void,
+}
+
+// String, array or vector member a component owns, by offset
+enum OwnedMember {
+    String(usize),
+    U32Array(usize),
+    F32Array(usize),
+    Vector { offset: usize, element_size: i32 },
+    StringVector(usize),
+}
+
+// Stored boxed in the binding_ctx of a component's type hooks
+struct ComponentHooks {
+    owned: Vec<OwnedMember>,
+    guest: flecs_type_hooks_t,
+}
+
+unsafe extern "C" fn component_hooks_free(binding_ctx: *mut c_void) {
+    drop(Box::from_raw(binding_ctx as *mut ComponentHooks));
+}
+
+unsafe fn component_hooks<'a>(type_info: *const ecs_type_info_t) -> &'a ComponentHooks {
+    &*((*type_info).hooks.binding_ctx as *const ComponentHooks)
+}
+
+// Owned member of a component created by flecs_component_create, by offset
+unsafe fn owned_member<'a>(component: ecs_entity_t, offset: u32) -> Option<&'a OwnedMember> {
+    let type_info = ecs_get_type_info(*WORLD, component);
+    if type_info.is_null() || (*type_info).hooks.binding_ctx.is_null() {
+        return None;
     }
+    component_hooks(type_info).owned.iter().find(|member| member.offset() == offset as usize)
 }
 
-#[no_mangle]
-pub unsafe fn flecs_delete_entity(entity: ecs_entity_t) {
-    let world = *WORLD;
-    let entity: ecs_entity_t = entity;
-    ecs_delete(world, entity);
+impl OwnedMember {
+    fn offset(&self) -> usize {
+        match self {
+            OwnedMember::String(offset)
+            | OwnedMember::U32Array(offset)
+            | OwnedMember::F32Array(offset)
+            | OwnedMember::Vector { offset, .. }
+            | OwnedMember::StringVector(offset) => *offset,
+        }
+    }
+
+    unsafe fn fini(&self, component_ptr: *mut u8) {
+        let member_ptr = component_ptr.add(self.offset());
+        match self {
+            OwnedMember::String(_) | OwnedMember::U32Array(_) | OwnedMember::F32Array(_) => {
+                os_free(*(member_ptr as *mut *mut c_void));
+            }
+            OwnedMember::Vector { element_size, .. } => {
+                ecs_vec_fini(std::ptr::null_mut(), member_ptr as *mut ecs_vec_t, *element_size);
+            }
+            OwnedMember::StringVector(_) => {
+                let vector = member_ptr as *mut ecs_vec_t;
+                string_vector_clear(vector);
+                ecs_vec_fini(std::ptr::null_mut(), vector, std::mem::size_of::<*mut c_char>() as i32);
+            }
+        }
+        self.forget(component_ptr);
+    }
+
+    // Replaces the member of dst, a bitwise copy of src, with a copy of its own
+    unsafe fn copy(&self, dst_ptr: *mut u8, src_ptr: *const u8) {
+        let dst = dst_ptr.add(self.offset());
+        let src = src_ptr.add(self.offset());
+        match self {
+            OwnedMember::String(_) => {
+                *(dst as *mut *mut c_char) = os_strdup(*(src as *const *const c_char));
+            }
+            OwnedMember::U32Array(_) => {
+                let value = *(src as *const *const u32);
+                let length = if value.is_null() { 0 } else { *value as usize };
+                *(dst as *mut *mut c_void) = array_dup(value as *const c_void, length);
+            }
+            OwnedMember::F32Array(_) => {
+                let value = *(src as *const *const f32);
+                let length = if value.is_null() { 0 } else { *value as usize };
+                *(dst as *mut *mut c_void) = array_dup(value as *const c_void, length);
+            }
+            OwnedMember::Vector { element_size, .. } => {
+                *(dst as *mut ecs_vec_t) =
+                    ecs_vec_copy_shrink(std::ptr::null_mut(), src as *const ecs_vec_t, *element_size);
+            }
+            OwnedMember::StringVector(_) => {
+                let element_size = std::mem::size_of::<*mut c_char>() as i32;
+                let vector = dst as *mut ecs_vec_t;
+                *vector = ecs_vec_copy_shrink(std::ptr::null_mut(), src as *const ecs_vec_t, element_size);
+                let strings = (*vector).array as *mut *mut c_char;
+                for index in 0..ecs_vec_count(vector) as usize {
+                    *strings.add(index) = os_strdup(*strings.add(index));
+                }
+            }
+        }
+    }
+
+    // Clears the member without freeing it, after its value was moved out
+    unsafe fn forget(&self, component_ptr: *mut u8) {
+        let member_ptr = component_ptr.add(self.offset());
+        match self {
+            OwnedMember::String(_) | OwnedMember::U32Array(_) | OwnedMember::F32Array(_) => {
+                *(member_ptr as *mut *mut c_void) = std::ptr::null_mut();
+            }
+            OwnedMember::Vector { .. } | OwnedMember::StringVector(_) => {
+                *(member_ptr as *mut ecs_vec_t) = MaybeUninit::zeroed().assume_init();
+            }
+        }
+    }
 }
 
-#[no_mangle]
-pub unsafe fn flecs_entity_has_component(entity: ecs_entity_t, component: ecs_entity_t) -> bool {
-    let world = *WORLD;
-    ecs_has_id(world, entity, component)
+unsafe fn owned_members_free(hooks: &ComponentHooks, component_ptr: *mut u8) {
+    for member in &hooks.owned {
+        member.fini(component_ptr);
+    }
 }
 
-#[no_mangle]
-pub unsafe fn flecs_is_valid(entity: ecs_entity_t) -> bool {
-    let world = *WORLD;
-    let entity: ecs_entity_t = entity;
-    ecs_is_valid(world, entity)
+unsafe extern "C" fn component_ctor(ptr: *mut c_void, count: i32, type_info: *const ecs_type_info_t) {
+    let hooks = component_hooks(type_info);
+    match hooks.guest.ctor {
+        Some(ctor) => ctor(ptr, count, (*type_info).size, hooks.guest.ctx),
+        None => std::ptr::write_bytes(ptr as *mut u8, 0, count as usize * (*type_info).size as usize),
+    }
 }
 
-#[no_mangle]
-pub unsafe fn m_free(ptr: *mut c_void) {
-    free(ptr as *mut c_void)
+unsafe extern "C" fn component_dtor(ptr: *mut c_void, count: i32, type_info: *const ecs_type_info_t) {
+    let hooks = component_hooks(type_info);
+    let size = (*type_info).size;
+    match hooks.guest.dtor {
+        Some(dtor) => dtor(ptr, count, size, hooks.guest.ctx),
+        None => {
+            for index in 0..count as usize {
+                owned_members_free(hooks, (ptr as *mut u8).add(index * size as usize));
+            }
+        }
+    }
 }
 
-#[no_mangle]
-pub unsafe fn flecs_component_set_member_ptr(
-    component_ptr: *mut c_void,
-    offset: u32,
-    value: *mut c_void,
+unsafe extern "C" fn component_copy(
+    dst_ptr: *mut c_void,
+    src_ptr: *const c_void,
+    count: i32,
+    type_info: *const ecs_type_info_t,
 ) {
-    let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut c_void;
-    *member_ptr = value;
+    let hooks = component_hooks(type_info);
+    let size = (*type_info).size;
+    match hooks.guest.copy {
+        Some(copy) => copy(dst_ptr, src_ptr, count, size, hooks.guest.ctx),
+        None => {
+            for index in 0..count as usize {
+                let dst = (dst_ptr as *mut u8).add(index * size as usize);
+                let src = (src_ptr as *mut u8).add(index * size as usize);
+                owned_members_free(hooks, dst);
+                std::ptr::copy_nonoverlapping(src, dst, size as usize);
+                for member in &hooks.owned {
+                    member.copy(dst, src);
+                }
+            }
+        }
+    }
 }
 
-#[no_mangle]
-pub unsafe fn flecs_component_get_member_ptr(
-    component_ptr: *mut c_void,
-    offset: u32,
-) -> *mut c_void {
-    let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut c_void;
-    *member_ptr as *mut c_void
+unsafe extern "C" fn component_move(
+    dst_ptr: *mut c_void,
+    src_ptr: *mut c_void,
+    count: i32,
+    type_info: *const ecs_type_info_t,
+) {
+    let hooks = component_hooks(type_info);
+    let size = (*type_info).size;
+    match hooks.guest.move_ {
+        Some(move_) => move_(dst_ptr, src_ptr, count, size, hooks.guest.ctx),
+        None => {
+            for index in 0..count as usize {
+                let dst = (dst_ptr as *mut u8).add(index * size as usize);
+                let src = (src_ptr as *mut u8).add(index * size as usize);
+                owned_members_free(hooks, dst);
+                std::ptr::copy_nonoverlapping(src, dst, size as usize);
+                // The source still gets destructed, so it can't keep the values
+                for member in &hooks.owned {
+                    member.forget(src);
+                }
+            }
+        }
+    }
 }
 
-#[no_mangle]
-// Trampoline closure from Rust using C callback and binding_ctx field to call a Rust closure
-pub unsafe extern "C" fn query_trampoline(iter: *mut ecs_iter_t) {
-    // println!("This system runs on this thread from trampoline: {}", std::thread::ThreadId::as_u64(&std::thread::current().id()));
-    // println!("Pthread ID from trampoline: {}", pthread_self());
-    let world = *WORLD;
-    let callback = (*iter).binding_ctx as *mut c_void;
-    if callback.is_null() {
-        return;
+unsafe extern "C" fn component_on_add(iter: *mut ecs_iter_t) {
+    let hooks = &*((*iter).binding_ctx as *const ComponentHooks);
+    if let Some(on_add) = hooks.guest.on_add {
+        on_add(iter, hooks.guest.ctx);
     }
-    let iter = toxoid_api::Iter::from(iter as *mut c_void);
-    let callback_fn: fn(&toxoid_api::Iter) = std::mem::transmute(callback);
-    callback_fn(&iter); // Call the callback through the reference
 }
 
-#[no_mangle]
-pub unsafe fn flecs_system_create(
-    callback: fn(*mut c_void)
-) -> *mut ecs_system_desc_t {
-    let mut system_desc: ecs_system_desc_t = MaybeUninit::zeroed().assume_init();
-    system_desc.binding_ctx = callback as *mut c_void;
-    system_desc.callback = Some(query_trampoline);
-    #[cfg(feature = "multithread")] {
-        system_desc.multi_threaded = true;
+unsafe extern "C" fn component_on_set(iter: *mut ecs_iter_t) {
+    let hooks = &*((*iter).binding_ctx as *const ComponentHooks);
+    if let Some(on_set) = hooks.guest.on_set {
+        on_set(iter, hooks.guest.ctx);
     }
-    Box::into_raw(Box::new(system_desc))
 }
 
-#[no_mangle]
-pub unsafe fn flecs_system_build(
-    system_desc: *mut ecs_system_desc_t,
-) -> ecs_entity_t {
+unsafe extern "C" fn component_on_remove(iter: *mut ecs_iter_t) {
+    let hooks = &*((*iter).binding_ctx as *const ComponentHooks);
+    if let Some(on_remove) = hooks.guest.on_remove {
+        on_remove(iter, hooks.guest.ctx);
+    }
+}
+
+// Updates the ComponentHooks of a component, creating them on first use, and
+// points the flecs hooks at the callbacks that have something to do. The
+// binding_ctx of type hooks is only ever set here.
+unsafe fn component_hooks_update(component: ecs_entity_t, update: impl FnOnce(&mut ComponentHooks)) {
     let world = *WORLD;
-    let mut entity_desc: ecs_entity_desc_t = unsafe { MaybeUninit::zeroed().assume_init() };
-    // We have to add this pair so that the system is part of standard progress stage
-    entity_desc.add[0] = unsafe { ecs_make_pair(EcsDependsOn, EcsOnUpdate) };
-    (*system_desc).entity = ecs_entity_init(world, &entity_desc);
-    ecs_system_init(world, system_desc)
+    let current = ecs_get_hooks_id(world, component);
+    let hooks = if !current.is_null() && !(*current).binding_ctx.is_null() {
+        (*current).binding_ctx as *mut ComponentHooks
+    } else {
+        Box::into_raw(Box::new(ComponentHooks { owned: Vec::new(), guest: MaybeUninit::zeroed().assume_init() }))
+    };
+    update(&mut *hooks);
+
+    let owns_members = !(*hooks).owned.is_empty();
+    let guest = (*hooks).guest;
+    let mut type_hooks: ecs_type_hooks_t = MaybeUninit::zeroed().assume_init();
+    if owns_members || guest.ctor.is_some() {
+        type_hooks.ctor = Some(component_ctor);
+    }
+    if owns_members || guest.dtor.is_some() {
+        type_hooks.dtor = Some(component_dtor);
+    }
+    if owns_members || guest.copy.is_some() {
+        type_hooks.copy = Some(component_copy);
+    }
+    if owns_members || guest.move_.is_some() {
+        type_hooks.move_ = Some(component_move);
+    }
+    if guest.on_add.is_some() {
+        type_hooks.on_add = Some(component_on_add);
+    }
+    if guest.on_set.is_some() {
+        type_hooks.on_set = Some(component_on_set);
+    }
+    if guest.on_remove.is_some() {
+        type_hooks.on_remove = Some(component_on_remove);
+    }
+    type_hooks.binding_ctx = hooks as *mut c_void;
+    type_hooks.binding_ctx_free = Some(component_hooks_free);
+    ecs_set_hooks_id(world, component, &type_hooks);
 }
 
-#[no_mangle]
-pub unsafe fn flecs_query_from_system_desc(
-    system_desc: *mut ecs_system_desc_t
-) -> *mut ecs_query_desc_t {
-    &mut (*system_desc).query as *mut ecs_query_desc_t
+// Gives a component created by flecs_component_create the default hooks for
+// its string, array and vector members
+unsafe fn owned_members_init(component: ecs_entity_t, member_types: &[u8]) {
+    let world = *WORLD;
+    let reflected = ecs_get_id(world, component, FLECS_IDEcsStructID_) as *const EcsStruct;
+    let mut owned = Vec::new();
+    let mut index = 0;
+    ecs_vector_each::<ecs_member_t, _>(&(*reflected).members, |member| {
+        let offset = member.offset as usize;
+        let member_type = member_types[index];
+        if member_type == Type::String as u8 {
+            owned.push(OwnedMember::String(offset));
+        } else if member_type == Type::U32Array as u8 {
+            owned.push(OwnedMember::U32Array(offset));
+        } else if member_type == Type::F32Array as u8 {
+            owned.push(OwnedMember::F32Array(offset));
+        } else if member_type == Type::StringVector as u8 {
+            owned.push(OwnedMember::StringVector(offset));
+        } else if member_type >= Type::U8Vector as u8 && member_type < Type::StringVector as u8 {
+            let element_type = get_member_type(member_type - Type::U8Vector as u8);
+            let element_size = (*ecs_get_type_info(world, element_type)).size;
+            owned.push(OwnedMember::Vector { offset, element_size });
+        }
+        index += 1;
+    });
+    if !owned.is_empty() {
+        component_hooks_update(component, |hooks| hooks.owned = owned);
+    }
 }
 
+// Sets lifecycle hooks of a component, before it's added to any entity.
+// Callbacks left null keep their current behavior. A ctor, dtor, copy or move
+// replaces the default that manages string, array and vector members, so it
+// has to free and copy those itself. Returns false when the component is in use.
 #[no_mangle]
-pub unsafe fn flecs_component_lookup(name: *mut c_char) -> ecs_entity_t {
+pub unsafe extern "C" fn flecs_component_set_hooks(component: ecs_entity_t, hooks: *const flecs_type_hooks_t) -> bool {
     let world = *WORLD;
-    let component_id: ecs_entity_t = ecs_lookup(world, name);
-    component_id
+    if ecs_id_in_use(world, component) || ecs_id_in_use(world, ecs_make_pair(component, EcsWildcard)) {
+        error_set(ECS_ID_IN_USE, "can't set hooks of a component that is in use");
+        return false;
+    }
+    let new = *hooks;
+    component_hooks_update(component, |hooks| {
+        let guest = &mut hooks.guest;
+        guest.ctor = new.ctor.or(guest.ctor);
+        guest.dtor = new.dtor.or(guest.dtor);
+        guest.copy = new.copy.or(guest.copy);
+        guest.move_ = new.move_.or(guest.move_);
+        guest.on_add = new.on_add.or(guest.on_add);
+        guest.on_set = new.on_set.or(guest.on_set);
+        guest.on_remove = new.on_remove.or(guest.on_remove);
+        guest.ctx = new.ctx;
+    });
+    true
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_to_json(entity: ecs_entity_t) -> *mut c_char {
+pub unsafe extern "C" fn flecs_progress(delta_time: f32) -> bool {
     let world = *WORLD;
-    let mut json_desc: ecs_entity_to_json_desc_t = unsafe { MaybeUninit::zeroed().assume_init() };
-    json_desc.serialize_base = true;
-    json_desc.serialize_ids = true;
+    let result = if !TRACING.load(std::sync::atomic::Ordering::Relaxed) {
+        ecs_progress(world, delta_time)
+    } else {
+        let start = trace_now();
+        let result = ecs_progress(world, delta_time);
+        trace_frame(start, trace_now());
+        result
+    };
+    http_dequeue();
+    memory_frame();
+    result
+}
+
+// Span of a frame or system run in a trace recording, in nanoseconds of the
+// OS API clock
+struct TraceEvent {
+    name: String,
+    category: String,
+    thread: i32,
+    start: u64,
+    end: u64,
+}
+
+struct Trace {
+    path: std::path::PathBuf,
+    frames_left: i32,
+    events: Vec<TraceEvent>,
+}
+
+// Set while a trace is recording, so frames and systems only take the lock then
+static TRACING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+static TRACE: std::sync::Mutex<Option<Trace>> = std::sync::Mutex::new(None);
+
+unsafe fn trace_now() -> u64 {
+    ecs_os_api.now_.unwrap()()
+}
+
+unsafe fn trace_frame(start: u64, end: u64) {
+    let mut trace = TRACE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(recording) = trace.as_mut() else { return };
+    recording.events.push(TraceEvent {
+        name: "frame".to_string(),
+        category: "frame".to_string(),
+        thread: 0,
+        start,
+        end,
+    });
+    recording.frames_left -= 1;
+    if recording.frames_left == 0 {
+        let recording = trace.take().unwrap();
+        TRACING.store(false, std::sync::atomic::Ordering::Relaxed);
+        trace_finish(&recording);
+    }
+}
+
+// Writes a recording that ended. A file that can't be written is logged as an
+// error, which also becomes the last error of the thread.
+unsafe fn trace_finish(recording: &Trace) -> bool {
+    let Err(error) = trace_write(recording) else { return true };
+    let message = format!("failed to write trace to {}: {}", recording.path.display(), error);
+    let c_message = std::ffi::CString::new(message.as_str()).unwrap();
+    ecs_log_(-3, concat!(file!(), "\0").as_ptr() as *const c_char, line!() as i32, c"%s".as_ptr(), c_message.as_ptr());
+    error_set(ECS_OPERATION_FAILED, &message);
+    false
+}
+
+// The phase the pipeline runs the system in: the first entity with EcsPhase
+// up its DependsOn chain, like the pipeline query finds it. Returns 0 if the
+// system has no phase.
+unsafe fn system_phase(world: *const ecs_world_t, system: ecs_entity_t) -> ecs_entity_t {
+    let mut phase = 0;
+    let table = ecs_get_table(world, system);
+    if table.is_null() {
+        return 0;
+    }
+    let found = ecs_search_relation(
+        world,
+        table,
+        0,
+        EcsPhase,
+        EcsDependsOn,
+        EcsUp,
+        &mut phase,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+    );
+    if found < 0 {
+        0
+    } else {
+        phase
+    }
+}
+
+// Records a run of a system created by flecs_system_build, in the category of
+// the phase it runs in
+unsafe fn trace_system(iter: *const ecs_iter_t, start: u64, end: u64) {
+    let world = *WORLD;
+    let system = (*iter).system;
+    let name = ecs_get_name(world, system);
+    let name = if name.is_null() {
+        format!("system {}", system)
+    } else {
+        std::ffi::CStr::from_ptr(name).to_string_lossy().into_owned()
+    };
+    let phase = system_phase(world, system);
+    let phase_name = if phase == 0 { std::ptr::null() } else { ecs_get_name(world, phase) };
+    let category = if phase == 0 {
+        "system".to_string()
+    } else if phase_name.is_null() {
+        format!("phase {}", phase)
+    } else {
+        std::ffi::CStr::from_ptr(phase_name).to_string_lossy().into_owned()
+    };
+    let thread = ecs_get_stage_id((*iter).world);
+    if let Some(recording) = TRACE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).as_mut() {
+        recording.events.push(TraceEvent { name, category, thread, start, end });
+    }
+}
+
+// Writes the recording in the Chrome Trace Event format. Phases are spans over
+// the systems that ran in them back to back.
+fn trace_write(trace: &Trace) -> std::io::Result<()> {
+    let Some(origin) = trace.events.iter().map(|event| event.start).min() else {
+        return std::fs::write(&trace.path, "{\"traceEvents\":[]}");
+    };
+    let mut events: Vec<(&str, &str, i32, u64, u64)> = Vec::new();
+    let mut phase: Option<(&str, i32, u64, u64)> = None;
+    for event in &trace.events {
+        events.push((&event.name, &event.category, event.thread, event.start, event.end));
+        match &mut phase {
+            Some((name, thread, _, end)) if *name == event.category && *thread == event.thread => *end = event.end,
+            _ => {
+                if let Some((name, thread, start, end)) = phase.take() {
+                    events.push((name, "phase", thread, start, end));
+                }
+                // Frames end phases, and systems without one don't start any
+                if event.category != "frame" && event.category != "system" {
+                    phase = Some((&event.category, event.thread, event.start, event.end));
+                }
+            }
+        }
+    }
+    if let Some((name, thread, start, end)) = phase {
+        events.push((name, "phase", thread, start, end));
+    }
+
+    let events: Vec<String> = events
+        .iter()
+        .map(|(name, category, thread, start, end)| {
+            format!(
+                "{{\"name\":{},\"cat\":{},\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{},\"dur\":{}}}",
+                json_string(name),
+                json_string(category),
+                thread,
+                (start - origin) as f64 / 1000.0,
+                (end - start) as f64 / 1000.0,
+            )
+        })
+        .collect();
+    std::fs::write(
+        &trace.path,
+        format!("{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}\n", events.join(",\n")),
+    )
+}
+
+// Records the next frame_count frames of flecs_progress and writes them to
+// path as a Chrome Trace Event file. Only systems created with
+// flecs_system_build that keep its run action are timed, and their phase is
+// the one the pipeline runs them in, found up their DependsOn chain. Frames
+// run with ecs_progress directly aren't recorded. Returns false if a
+// recording is already running or path is null. If the file can't be written
+// after the last frame, the error is logged and set as the last error of the
+// thread that ran it.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_trace_start(frame_count: i32, path: *const c_char) -> bool {
+    if path.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "path is null");
+        return false;
+    }
+    let mut trace = TRACE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if trace.is_some() || frame_count <= 0 {
+        error_set(ECS_INVALID_OPERATION, "a trace is recording or the frame count isn't positive");
+        return false;
+    }
+    let path = std::ffi::CStr::from_ptr(path).to_string_lossy().into_owned();
+    *trace = Some(Trace { path: path.into(), frames_left: frame_count, events: Vec::new() });
+    TRACING.store(true, std::sync::atomic::Ordering::Relaxed);
+    true
+}
+
+// Ends a recording before its frames ran out and writes it. Returns false if
+// there was no recording or the file couldn't be written.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_trace_stop() -> bool {
+    let Some(recording) = TRACE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() else {
+        return false;
+    };
+    TRACING.store(false, std::sync::atomic::Ordering::Relaxed);
+    trace_finish(&recording)
+}
+
+// Statistics of the world and of the systems in its pipeline, sampled with
+// flecs_stats_update, e.g. once per frame after flecs_progress
+pub struct flecs_stats_t {
+    world: ecs_world_stats_t,
+    pipeline: ecs_pipeline_stats_t,
+}
+
+// Counters of the last sample. Things that happen (frames, systems ran, time
+// spent) are counted since the sample before it, times are in seconds.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct flecs_world_stats_t {
+    pub entity_count: i32,
+    pub not_alive_entity_count: i32,
+    pub component_count: i32,
+    pub tag_count: i32,
+    pub pair_count: i32,
+    pub table_count: i32,
+    pub empty_table_count: i32,
+    pub query_count: i32,
+    pub observer_count: i32,
+    pub system_count: i32,
+    pub frame_count: i32,
+    pub merge_count: i32,
+    pub systems_ran: i32,
+    pub observers_ran: i32,
+    pub frame_time: f64,
+    pub system_time: f64,
+    pub emit_time: f64,
+    pub merge_time: f64,
+    pub delta_time: f64,
+    pub world_time: f64,
+    pub fps: f64,
+}
+
+// One system of the pipeline in the last sample, in the order systems run
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct flecs_system_stats_t {
+    pub system: ecs_entity_t,
+    pub time_spent: f64,
+    pub invoke_count: i32,
+    pub matched_entity_count: i32,
+    pub matched_table_count: i32,
+}
+
+// The gauge of a counter holds its increase since the previous sample
+unsafe fn gauge(metric: &ecs_metric_t, t: i32) -> f64 {
+    metric.gauge.avg[t as usize] as f64
+}
+
+unsafe fn counter_total(metric: &ecs_metric_t, t: i32) -> f64 {
+    metric.counter.value[t as usize]
+}
+
+// Measures frame and system time from here on, which the stats need
+#[no_mangle]
+pub unsafe extern "C" fn flecs_stats_create() -> *mut flecs_stats_t {
+    let world = *WORLD;
+    ecs_measure_frame_time(world, true);
+    ecs_measure_system_time(world, true);
+    Box::into_raw(Box::new(flecs_stats_t {
+        world: MaybeUninit::zeroed().assume_init(),
+        pipeline: MaybeUninit::zeroed().assume_init(),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_stats_update(stats: *mut flecs_stats_t) {
+    let world = *WORLD;
+    ecs_world_stats_get(world, &mut (*stats).world);
+    let pipeline = ecs_get_pipeline(world);
+    if pipeline != 0 {
+        ecs_pipeline_stats_get(world, pipeline, &mut (*stats).pipeline);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_stats_world(stats: *const flecs_stats_t, world_stats: *mut flecs_world_stats_t) {
+    let stats = &(*stats).world;
+    let t = stats.t;
+    *world_stats = flecs_world_stats_t {
+        entity_count: gauge(&stats.entities.count, t) as i32,
+        not_alive_entity_count: gauge(&stats.entities.not_alive_count, t) as i32,
+        component_count: gauge(&stats.components.component_count, t) as i32,
+        tag_count: gauge(&stats.components.tag_count, t) as i32,
+        pair_count: gauge(&stats.components.pair_count, t) as i32,
+        table_count: gauge(&stats.tables.count, t) as i32,
+        empty_table_count: gauge(&stats.tables.empty_count, t) as i32,
+        query_count: gauge(&stats.queries.query_count, t) as i32,
+        observer_count: gauge(&stats.queries.observer_count, t) as i32,
+        system_count: gauge(&stats.queries.system_count, t) as i32,
+        frame_count: gauge(&stats.frame.frame_count, t) as i32,
+        merge_count: gauge(&stats.frame.merge_count, t) as i32,
+        systems_ran: gauge(&stats.frame.systems_ran, t) as i32,
+        observers_ran: gauge(&stats.frame.observers_ran, t) as i32,
+        frame_time: gauge(&stats.performance.frame_time, t),
+        system_time: gauge(&stats.performance.system_time, t),
+        emit_time: gauge(&stats.performance.emit_time, t),
+        merge_time: gauge(&stats.performance.merge_time, t),
+        delta_time: gauge(&stats.performance.delta_time, t),
+        world_time: counter_total(&stats.performance.world_time, t),
+        fps: gauge(&stats.performance.fps, t),
+    };
+}
+
+// Returns a malloc'd array of the systems the pipeline ran, which the caller
+// releases with m_free, and writes their count. Null when there are none.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_stats_systems(stats: *const flecs_stats_t, count: *mut i32) -> *mut flecs_system_stats_t {
+    let pipeline = &(*stats).pipeline;
+    let mut systems = Vec::new();
+    // Merges between systems are stored as 0
+    ecs_vector_each::<ecs_entity_t, _>(&pipeline.systems, |system| {
+        if *system == 0 {
+            return;
+        }
+        let system_stats = ecs_map_get_deref_(&pipeline.system_stats, *system) as *const ecs_system_stats_t;
+        if system_stats.is_null() {
+            return;
+        }
+        let system_stats = &*system_stats;
+        let t = system_stats.query.t;
+        systems.push(flecs_system_stats_t {
+            system: *system,
+            time_spent: gauge(&system_stats.time_spent, t),
+            invoke_count: gauge(&system_stats.invoke_count, t) as i32,
+            matched_entity_count: gauge(&system_stats.query.matched_entity_count, t) as i32,
+            matched_table_count: gauge(&system_stats.query.matched_table_count, t) as i32,
+        });
+    });
+    *count = systems.len() as i32;
+    slice_to_malloc(&systems)
+}
+
+// The world and system stats of the last sample as JSON. Returns a malloc'd string the caller releases with m_free.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_stats_to_json(stats: *const flecs_stats_t) -> *mut c_char {
+    let world = *WORLD;
+    let mut world_stats = flecs_world_stats_t::default();
+    flecs_stats_world(stats, &mut world_stats);
+    let w = world_stats;
+    let fields = [
+        ("entity_count", w.entity_count as f64),
+        ("not_alive_entity_count", w.not_alive_entity_count as f64),
+        ("component_count", w.component_count as f64),
+        ("tag_count", w.tag_count as f64),
+        ("pair_count", w.pair_count as f64),
+        ("table_count", w.table_count as f64),
+        ("empty_table_count", w.empty_table_count as f64),
+        ("query_count", w.query_count as f64),
+        ("observer_count", w.observer_count as f64),
+        ("system_count", w.system_count as f64),
+        ("frame_count", w.frame_count as f64),
+        ("merge_count", w.merge_count as f64),
+        ("systems_ran", w.systems_ran as f64),
+        ("observers_ran", w.observers_ran as f64),
+        ("frame_time", w.frame_time),
+        ("system_time", w.system_time),
+        ("emit_time", w.emit_time),
+        ("merge_time", w.merge_time),
+        ("delta_time", w.delta_time),
+        ("world_time", w.world_time),
+        ("fps", w.fps),
+    ];
+    let fields: Vec<String> =
+        fields.iter().map(|(name, value)| format!("{}:{}", json_string(name), json_number(*value))).collect();
+    let mut json = format!("{{\"world\":{{{}}}", fields.join(","));
+
+    let mut count = 0;
+    let systems = flecs_stats_systems(stats, &mut count);
+    json.push_str(",\"systems\":[");
+    for index in 0..count as usize {
+        let system = *systems.add(index);
+        let name = ecs_get_name(world, system.system);
+        let name = if name.is_null() {
+            "null".to_string()
+        } else {
+            json_string(&std::ffi::CStr::from_ptr(name).to_string_lossy())
+        };
+        if index > 0 {
+            json.push(',');
+        }
+        json.push_str(&format!(
+            "{{\"id\":{},\"name\":{},\"time_spent\":{},\"invoke_count\":{},\"matched_entity_count\":{},\"matched_table_count\":{}}}",
+            system.system,
+            name,
+            json_number(system.time_spent),
+            system.invoke_count,
+            system.matched_entity_count,
+            system.matched_table_count,
+        ));
+    }
+    free(systems as *mut c_void);
+    json.push_str("]}");
+
+    let json = std::ffi::CString::new(json).unwrap();
+    let json_ptr = malloc(json.as_bytes_with_nul().len()) as *mut c_char;
+    std::ptr::copy_nonoverlapping(json.as_ptr(), json_ptr, json.as_bytes_with_nul().len());
+    json_ptr
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_stats_destroy(stats: *mut flecs_stats_t) {
+    ecs_pipeline_stats_fini(&mut (*stats).pipeline);
+    drop(Box::from_raw(stats));
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MetricKind {
+    Gauge,
+    Counter,
+    CounterIncrement,
+    CounterId,
+}
+
+unsafe fn get_metric_kind(kind: u8) -> Option<ecs_entity_t> {
+    match kind {
+        0 => Some(EcsGauge),
+        1 => Some(EcsCounter),
+        2 => Some(EcsCounterIncrement),
+        3 => Some(EcsCounterId),
+        _ => None,
+    }
+}
+
+// The metrics addon isn't imported by ecs_init, and the ids of the metric
+// kinds only exist once it is. Importing again is a no-op.
+unsafe fn metrics_import() {
+    let world = *WORLD;
+    ecs_import_c(world, Some(FlecsMetricsImport), c"FlecsMetrics".as_ptr());
+}
+
+unsafe fn metric_create(name: *const c_char, mut desc: ecs_metric_desc_t) -> ecs_entity_t {
+    let world = *WORLD;
+    let mut entity_desc: ecs_entity_desc_t = MaybeUninit::zeroed().assume_init();
+    entity_desc.name = name;
+    desc.entity = ecs_entity_init(world, &entity_desc);
+    let metric = ecs_metric_init(world, &desc);
+    if metric == 0 {
+        ecs_delete(world, desc.entity);
+    }
+    metric
+}
+
+// Metric on a member of a component, e.g. "health" or "stats.health" of
+// Enemy, measured on every entity with the component. Metrics update during
+// flecs_progress. Returns 0 if the member doesn't exist or can't be measured,
+// or if the kind is unknown.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_metric_create_member(
+    name: *const c_char,
+    component: ecs_entity_t,
+    member: *const c_char,
+    kind: u8,
+) -> ecs_entity_t {
+    metrics_import();
+    let Some(kind) = get_metric_kind(kind) else {
+        error_set(ECS_INVALID_PARAMETER, "unknown metric kind");
+        return 0;
+    };
+    let mut desc: ecs_metric_desc_t = MaybeUninit::zeroed().assume_init();
+    desc.id = component;
+    desc.dotmember = member;
+    desc.kind = kind;
+    metric_create(name, desc)
+}
+
+// Metric on whether entities have an id. A gauge is 1 for each entity with
+// the id, CounterId accumulates the number of entities times the frame time.
+// With targets, an (R, *) pair gets a value per target. Returns 0 if the
+// metric can't be created or the kind is unknown.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_metric_create_id(
+    name: *const c_char,
+    id: ecs_id_t,
+    kind: u8,
+    targets: bool,
+) -> ecs_entity_t {
+    metrics_import();
+    let Some(kind) = get_metric_kind(kind) else {
+        error_set(ECS_INVALID_PARAMETER, "unknown metric kind");
+        return 0;
+    };
+    let mut desc: ecs_metric_desc_t = MaybeUninit::zeroed().assume_init();
+    desc.id = id;
+    desc.targets = targets;
+    desc.kind = kind;
+    metric_create(name, desc)
+}
+
+// Value of a metric for one entity, or one target of a metric with targets
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct flecs_metric_instance_t {
+    pub source: ecs_entity_t,
+    pub value: f64,
+}
+
+// Values of a metric over all of its instances
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct flecs_metric_value_t {
+    pub count: i32,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+// Instances are children of the metric, except for a CounterId metric without
+// targets, which stores its value on the metric itself
+unsafe fn metric_instances(metric: ecs_entity_t) -> Vec<flecs_metric_instance_t> {
+    let world = *WORLD;
+    let mut instances = Vec::new();
+    let value = ecs_get_id(world, metric, FLECS_IDEcsMetricValueID_) as *const EcsMetricValue;
+    if !value.is_null() {
+        instances.push(flecs_metric_instance_t { source: metric, value: (*value).value });
+    }
+    let mut iter = ecs_children(world, metric);
+    while ecs_children_next(&mut iter) {
+        for index in 0..iter.count as usize {
+            let instance = *iter.entities.add(index);
+            let value = ecs_get_id(world, instance, FLECS_IDEcsMetricValueID_) as *const EcsMetricValue;
+            if value.is_null() {
+                continue;
+            }
+            let source = ecs_get_id(world, instance, FLECS_IDEcsMetricSourceID_) as *const EcsMetricSource;
+            let source = if source.is_null() { instance } else { (*source).entity };
+            instances.push(flecs_metric_instance_t { source, value: (*value).value });
+        }
+    }
+    instances
+}
+
+// Returns false if the metric has no instances
+#[no_mangle]
+pub unsafe extern "C" fn flecs_metric_value(metric: ecs_entity_t, value: *mut flecs_metric_value_t) -> bool {
+    let instances = metric_instances(metric);
+    if instances.is_empty() {
+        *value = flecs_metric_value_t::default();
+        return false;
+    }
+    let values = instances.iter().map(|instance| instance.value);
+    let sum: f64 = values.clone().sum();
+    *value = flecs_metric_value_t {
+        count: instances.len() as i32,
+        sum,
+        min: values.clone().fold(f64::INFINITY, f64::min),
+        max: values.fold(f64::NEG_INFINITY, f64::max),
+        avg: sum / instances.len() as f64,
+    };
+    true
+}
+
+// Returns a malloc'd array of the metric's instances, which the caller
+// releases with m_free, and writes their count. Null when there are none.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_metric_instances(metric: ecs_entity_t, count: *mut i32) -> *mut flecs_metric_instance_t {
+    let instances = metric_instances(metric);
+    *count = instances.len() as i32;
+    slice_to_malloc(&instances)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+unsafe fn get_alert_severity(severity: u8) -> Option<ecs_entity_t> {
+    match severity {
+        0 => Some(EcsAlertInfo),
+        1 => Some(EcsAlertWarning),
+        2 => Some(EcsAlertError),
+        3 => Some(EcsAlertCritical),
+        _ => None,
+    }
+}
+
+fn get_alert_severity_code(severity: ecs_entity_t) -> u8 {
+    unsafe {
+        match severity {
+            s if s == EcsAlertInfo => AlertSeverity::Info as u8,
+            s if s == EcsAlertWarning => AlertSeverity::Warning as u8,
+            s if s == EcsAlertError => AlertSeverity::Error as u8,
+            _ => AlertSeverity::Critical as u8,
+        }
+    }
+}
+
+// Alert raised for every entity matching a query expression such as
+// "Health, !Position". The message may refer to query variables, e.g.
+// "$this has Health but no Position". Alerts are evaluated every half second
+// of world time during flecs_progress, and an alert that stops matching is
+// cleared after retain_period seconds. Returns 0 if the query doesn't parse
+// or the severity is unknown.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_alert_create(
+    name: *const c_char,
+    expr: *const c_char,
+    message: *const c_char,
+    severity: u8,
+    retain_period: f32,
+) -> ecs_entity_t {
+    let world = *WORLD;
+    // Also imports the metrics addon, which alert instances use. The severity
+    // ids only exist once the addon is imported.
+    ecs_import_c(world, Some(FlecsAlertsImport), c"FlecsAlerts".as_ptr());
+    let Some(severity) = get_alert_severity(severity) else {
+        error_set(ECS_INVALID_PARAMETER, "unknown alert severity");
+        return 0;
+    };
+
+    let mut entity_desc: ecs_entity_desc_t = MaybeUninit::zeroed().assume_init();
+    entity_desc.name = name;
+    let mut desc: ecs_alert_desc_t = MaybeUninit::zeroed().assume_init();
+    desc.entity = ecs_entity_init(world, &entity_desc);
+    desc.filter.expr = expr;
+    desc.message = message;
+    desc.severity = severity;
+    desc.retain_period = retain_period;
+    let alert = ecs_alert_init(world, &desc);
+    if alert == 0 {
+        ecs_delete(world, desc.entity);
+    }
+    alert
+}
+
+// Number of active alerts for an entity, or whether one alert is active for
+// it if alert isn't 0
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_alert_count(entity: ecs_entity_t, alert: ecs_entity_t) -> i32 {
+    let world = *WORLD;
+    ecs_get_alert_count(world, entity, alert)
+}
+
+// Active alert for one entity. The message is owned by the alert instance and
+// is valid until the next flecs_progress.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct flecs_alert_instance_t {
+    pub alert: ecs_entity_t,
+    pub entity: ecs_entity_t,
+    pub severity: u8,
+    pub duration: f64,
+    pub message: *const c_char,
+}
+
+// Returns a malloc'd array of the active instances of an alert, or of all
+// alerts if alert is 0, which the caller releases with m_free, and writes
+// their count. Null when there are none.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_alerts_active(alert: ecs_entity_t, count: *mut i32) -> *mut flecs_alert_instance_t {
+    let world = *WORLD;
+    let mut instances = Vec::new();
+    if FLECS_IDEcsAlertInstanceID_ != 0 {
+        let mut term: ecs_term_t = MaybeUninit::zeroed().assume_init();
+        term.id = if alert == 0 { FLECS_IDEcsAlertInstanceID_ } else { ecs_make_pair(EcsChildOf, alert) };
+        let mut iter = ecs_term_iter(world, &mut term);
+        while ecs_term_next(&mut iter) {
+            for index in 0..iter.count as usize {
+                let instance = *iter.entities.add(index);
+                let alert_instance =
+                    ecs_get_id(world, instance, FLECS_IDEcsAlertInstanceID_) as *const EcsAlertInstance;
+                if alert_instance.is_null() {
+                    continue;
+                }
+                let source = ecs_get_id(world, instance, FLECS_IDEcsMetricSourceID_) as *const EcsMetricSource;
+                let value = ecs_get_id(world, instance, FLECS_IDEcsMetricValueID_) as *const EcsMetricValue;
+                instances.push(flecs_alert_instance_t {
+                    alert: ecs_get_target(world, instance, EcsChildOf, 0),
+                    entity: if source.is_null() { 0 } else { (*source).entity },
+                    severity: get_alert_severity_code(ecs_get_target(world, instance, FLECS_IDEcsAlertID_, 0)),
+                    duration: if value.is_null() { 0.0 } else { (*value).value },
+                    message: (*alert_instance).message,
+                });
+            }
+        }
+    }
+    *count = instances.len() as i32;
+    slice_to_malloc(&instances)
+}
+
+// REST server for the flecs explorer. Native builds can listen on a port,
+// wasm builds have no sockets and dispatch requests with flecs_rest_request.
+static mut REST_SERVER: *mut ecs_http_server_t = std::ptr::null_mut();
+static mut REST_LISTENING: bool = false;
+
+unsafe fn rest_server(port: u16) -> *mut ecs_http_server_t {
+    let world = *WORLD;
+    if REST_SERVER.is_null() {
+        let mut desc: ecs_http_server_desc_t = MaybeUninit::zeroed().assume_init();
+        desc.port = port;
+        desc.cache_timeout = 1.0;
+        REST_SERVER = ecs_rest_server_init(world, &desc);
+    }
+    REST_SERVER
+}
+
+// Requests received on a socket are queued, and answered on the main thread
+// after each frame
+unsafe fn http_dequeue() {
+    let world = *WORLD;
+    let delta_time = (*ecs_get_world_info(world)).delta_time;
+    if REST_LISTENING {
+        ecs_http_server_dequeue(REST_SERVER, delta_time);
+    }
+    if HTTP_LISTENING {
+        ecs_http_server_dequeue(HTTP_SERVER, delta_time);
+    }
+}
+
+// Answers a request without a socket. Returns the body, or null if the reply
+// has no body.
+unsafe fn http_server_request(
+    server: *mut ecs_http_server_t,
+    method: *const c_char,
+    path: *const c_char,
+    code: *mut i32,
+) -> *mut c_char {
+    if server.is_null() {
+        *code = 500;
+        return std::ptr::null_mut();
+    }
+    let mut reply: ecs_http_reply_t = MaybeUninit::zeroed().assume_init();
+    reply.code = 200;
+    reply.status = c"OK".as_ptr();
+    reply.content_type = c"application/json".as_ptr();
+    // Fails without setting the code if the request is too long
+    if ecs_http_server_request(server, method, path, &mut reply) != 0 && reply.code < 400 {
+        reply.code = 400;
+    }
+    ecs_strbuf_reset(&mut reply.headers);
+    *code = reply.code;
+    os_string_to_malloc(ecs_strbuf_get(&mut reply.body))
+}
+
+// Listens on a port, 27750 if 0. Requests are answered during flecs_progress.
+// Returns false if the port can't be opened or the server already listens.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn flecs_rest_enable(port: u16) -> bool {
+    if REST_LISTENING {
+        error_set(ECS_INVALID_OPERATION, "the REST server already listens");
+        return false;
+    }
+    // A server created for dispatch has no port, so start over
+    flecs_rest_disable();
+    let server = rest_server(if port == 0 { ECS_REST_DEFAULT_PORT as u16 } else { port });
+    if server.is_null() || ecs_http_server_start(server) != 0 {
+        flecs_rest_disable();
+        return false;
+    }
+    REST_LISTENING = true;
+    true
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_rest_disable() {
+    if REST_SERVER.is_null() {
+        return;
+    }
+    if REST_LISTENING {
+        ecs_http_server_stop(REST_SERVER);
+        REST_LISTENING = false;
+    }
+    ecs_rest_server_fini(REST_SERVER);
+    REST_SERVER = std::ptr::null_mut();
+}
+
+// Answers a REST request such as ("GET", "/entity/flecs/core/World") without
+// a socket. Writes the HTTP status code and returns the body, which the caller
+// releases with m_free, or null if the reply has no body.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_rest_request(method: *const c_char, path: *const c_char, code: *mut i32) -> *mut c_char {
+    http_server_request(rest_server(0), method, path, code)
+}
+
+// Custom routes such as GET /game/spawn, handled by guest callbacks on a
+// server of their own
+static mut HTTP_SERVER: *mut ecs_http_server_t = std::ptr::null_mut();
+static mut HTTP_LISTENING: bool = false;
+static HTTP_ROUTES: std::sync::Mutex<Vec<HttpRoute>> = std::sync::Mutex::new(Vec::new());
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+// Handles a request by writing to the reply, see flecs_http_reply_set. Returns
+// false to answer with 404.
+pub type flecs_http_callback_t =
+    unsafe extern "C" fn(request: *const ecs_http_request_t, reply: *mut ecs_http_reply_t, ctx: *mut c_void) -> bool;
+
+// Host function that runs a guest route handler by its index in the wasm
+// function table, like flecs_callback_dispatcher_t for systems
+pub type flecs_http_dispatcher_t = unsafe extern "C" fn(
+    callback_index: u32,
+    request: *const ecs_http_request_t,
+    reply: *mut ecs_http_reply_t,
+    ctx: *mut c_void,
+) -> bool;
+
+static mut HTTP_DISPATCHER: Option<flecs_http_dispatcher_t> = None;
+
+#[derive(Clone, Copy)]
+enum HttpHandler {
+    Function { callback: flecs_http_callback_t, ctx: *mut c_void },
+    Indexed { callback_index: u32, ctx: *mut c_void },
+}
+
+struct HttpRoute {
+    method: ecs_http_method_t,
+    // Without the leading slash, like the path of a request
+    path: Vec<u8>,
+    handler: HttpHandler,
+}
+
+// The contexts belong to the guest, which only calls back on the main thread
+unsafe impl Send for HttpRoute {}
+
+unsafe extern "C" fn http_route_reply(
+    request: *const ecs_http_request_t,
+    reply: *mut ecs_http_reply_t,
+    _ctx: *mut c_void,
+) -> bool {
+    let path = std::ffi::CStr::from_ptr((*request).path).to_bytes();
+    // Copied out so that handlers can register routes
+    // A route that panicked on another thread doesn't take the others down
+    let handler = HTTP_ROUTES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .find(|route| route.method == (*request).method && route.path == path)
+        .map(|route| route.handler);
+    match handler {
+        Some(HttpHandler::Function { callback, ctx }) => callback(request, reply, ctx),
+        Some(HttpHandler::Indexed { callback_index, ctx }) => match HTTP_DISPATCHER {
+            Some(dispatcher) => dispatcher(callback_index, request, reply, ctx),
+            None => false,
+        },
+        None => false,
+    }
+}
+
+unsafe fn http_server(port: u16) -> *mut ecs_http_server_t {
+    // The server allocates with the OS API, which is set up with the world
+    let _ = *WORLD;
+    if HTTP_SERVER.is_null() {
+        let mut desc: ecs_http_server_desc_t = MaybeUninit::zeroed().assume_init();
+        desc.callback = Some(http_route_reply);
+        desc.port = port;
+        HTTP_SERVER = ecs_http_server_init(&desc);
+    }
+    HTTP_SERVER
+}
+
+unsafe fn http_route_add(method: u8, path: *const c_char, handler: HttpHandler) {
+    let path = std::ffi::CStr::from_ptr(path).to_bytes();
+    let path = path.strip_prefix(b"/").unwrap_or(path).to_vec();
+    let method = method as ecs_http_method_t;
+    let mut routes = HTTP_ROUTES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    routes.retain(|route| route.method != method || route.path != path);
+    routes.push(HttpRoute { method, path, handler });
+}
+
+// Registers a route, replacing the handler of an existing route with the same
+// method and path. Query parameters aren't part of the path.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_route(
+    method: u8,
+    path: *const c_char,
+    callback: flecs_http_callback_t,
+    ctx: *mut c_void,
+) {
+    http_route_add(method, path, HttpHandler::Function { callback, ctx });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_route_indexed(
+    method: u8,
+    path: *const c_char,
+    callback_index: u32,
+    ctx: *mut c_void,
+) {
+    http_route_add(method, path, HttpHandler::Indexed { callback_index, ctx });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_route_remove(method: u8, path: *const c_char) {
+    let path = std::ffi::CStr::from_ptr(path).to_bytes();
+    let path = path.strip_prefix(b"/").unwrap_or(path);
+    let method = method as ecs_http_method_t;
+    HTTP_ROUTES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .retain(|route| route.method != method || route.path != path);
+}
+
+// Registers the host function that runs handlers created with
+// flecs_http_route_indexed
+#[no_mangle]
+pub unsafe extern "C" fn flecs_set_http_dispatcher(dispatcher: Option<flecs_http_dispatcher_t>) {
+    HTTP_DISPATCHER = dispatcher;
+}
+
+// Listens for the custom routes on a port. Requests are answered during
+// flecs_progress. Returns false if the port can't be opened or the server
+// already listens.
+#[cfg(not(target_arch = "wasm32"))]
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_enable(port: u16) -> bool {
+    if HTTP_LISTENING || port == 0 {
+        error_set(ECS_INVALID_OPERATION, "the HTTP server already listens or the port is 0");
+        return false;
+    }
+    // A server created for dispatch has no port, so start over
+    flecs_http_disable();
+    let server = http_server(port);
+    if server.is_null() || ecs_http_server_start(server) != 0 {
+        flecs_http_disable();
+        return false;
+    }
+    HTTP_LISTENING = true;
+    true
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_disable() {
+    if HTTP_SERVER.is_null() {
+        return;
+    }
+    if HTTP_LISTENING {
+        ecs_http_server_stop(HTTP_SERVER);
+        HTTP_LISTENING = false;
+    }
+    ecs_http_server_fini(HTTP_SERVER);
+    HTTP_SERVER = std::ptr::null_mut();
+}
+
+// Answers a request for a custom route, like flecs_rest_request
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_request(method: *const c_char, path: *const c_char, code: *mut i32) -> *mut c_char {
+    http_server_request(http_server(0), method, path, code)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_request_method(request: *const ecs_http_request_t) -> u8 {
+    (*request).method as u8
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_request_path(request: *const ecs_http_request_t) -> *const c_char {
+    (*request).path
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_request_body(request: *const ecs_http_request_t) -> *const c_char {
+    (*request).body
+}
+
+// Decoded value of a query parameter, or null if the request doesn't have it
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_request_param(request: *const ecs_http_request_t, name: *const c_char) -> *const c_char {
+    ecs_http_get_param(request, name)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_request_header(request: *const ecs_http_request_t, name: *const c_char) -> *const c_char {
+    ecs_http_get_header(request, name)
+}
+
+// Sets the status code of a reply and appends to its JSON body. The body is
+// copied.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_http_reply_set(reply: *mut ecs_http_reply_t, code: i32, body: *const c_char) {
+    (*reply).code = code;
+    (*reply).status = match code {
+        200..=299 => c"OK".as_ptr(),
+        400 => c"Bad Request".as_ptr(),
+        404 => c"Not Found".as_ptr(),
+        _ => c"Error".as_ptr(),
+    };
+    if !body.is_null() {
+        ecs_strbuf_appendstr(&mut (*reply).body, body);
+    }
+}
+
+// Receives flecs log messages. Levels are 0 for trace, above 0 for debug
+// output, -2 for warnings, -3 for errors and -4 for fatal errors. The file
+// may be null.
+pub type flecs_log_callback_t =
+    unsafe extern "C" fn(level: i32, file: *const c_char, line: i32, msg: *const c_char, ctx: *mut c_void);
+
+// Host function that runs a guest log callback by its index in the wasm
+// function table, like flecs_callback_dispatcher_t for systems
+pub type flecs_log_dispatcher_t = unsafe extern "C" fn(
+    callback_index: u32,
+    level: i32,
+    file: *const c_char,
+    line: i32,
+    msg: *const c_char,
+    ctx: *mut c_void,
+);
+
+static mut LOG_DISPATCHER: Option<flecs_log_dispatcher_t> = None;
+
+#[derive(Clone, Copy)]
+enum LogSink {
+    // The flecs console logger, which the color and timestamp settings apply to
+    Console,
+    Rust,
+    Function { callback: flecs_log_callback_t, ctx: *mut c_void },
+    Indexed { callback_index: u32, ctx: *mut c_void },
+}
+
+static mut LOG_SINK: LogSink = LogSink::Console;
+// Set when the world is created
+static mut LOG_CONSOLE: ecs_os_api_log_t = None;
+
+unsafe extern "C" fn log_trampoline(level: i32, file: *const c_char, line: i32, msg: *const c_char) {
+    if level <= -3 {
+        error_record(level, msg);
+    }
+    match LOG_SINK {
+        LogSink::Console => {
+            if let Some(console) = LOG_CONSOLE {
+                console(level, file, line, msg);
+            }
+        }
+        LogSink::Rust => {
+            let log_level = match level {
+                i32::MIN..=-3 => log::Level::Error,
+                -2 => log::Level::Warn,
+                -1..=0 => log::Level::Info,
+                1 => log::Level::Debug,
+                _ => log::Level::Trace,
+            };
+            if log_level > log::max_level() {
+                return;
+            }
+            let file = if file.is_null() { None } else { std::ffi::CStr::from_ptr(file).to_str().ok() };
+            let msg = std::ffi::CStr::from_ptr(msg).to_string_lossy();
+            log::logger().log(
+                &log::Record::builder()
+                    .level(log_level)
+                    .target("flecs")
+                    .file(file)
+                    .line(Some(line as u32))
+                    .args(format_args!("{}", msg))
+                    .build(),
+            );
+        }
+        LogSink::Function { callback, ctx } => callback(level, file, line, msg, ctx),
+        LogSink::Indexed { callback_index, ctx } => {
+            if let Some(dispatcher) = LOG_DISPATCHER {
+                dispatcher(callback_index, level, file, line, msg, ctx);
+            }
+        }
+    }
+}
+
+// Messages above the level aren't logged. Returns the previous level.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_set_level(level: i32) -> i32 {
+    let _ = *WORLD;
+    ecs_log_set_level(level)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_get_level() -> i32 {
+    let _ = *WORLD;
+    ecs_log_get_level()
+}
+
+// Returns the previous setting
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_enable_colors(enabled: bool) -> bool {
+    let _ = *WORLD;
+    ecs_log_enable_colors(enabled)
+}
+
+// Returns the previous setting
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_enable_timestamp(enabled: bool) -> bool {
+    let _ = *WORLD;
+    ecs_log_enable_timestamp(enabled)
+}
+
+// Sends log messages to a callback instead of the console, or back to the
+// console if the callback is null
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_set_callback(callback: Option<flecs_log_callback_t>, ctx: *mut c_void) {
+    LOG_SINK = match callback {
+        Some(callback) => LogSink::Function { callback, ctx },
+        None => LogSink::Console,
+    };
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_set_callback_indexed(callback_index: u32, ctx: *mut c_void) {
+    LOG_SINK = LogSink::Indexed { callback_index, ctx };
+}
+
+// Registers the host function that runs callbacks set with
+// flecs_log_set_callback_indexed
+#[no_mangle]
+pub unsafe extern "C" fn flecs_set_log_dispatcher(dispatcher: Option<flecs_log_dispatcher_t>) {
+    LOG_DISPATCHER = dispatcher;
+}
+
+// Sends log messages to the logger of the log crate, with "flecs" as target
+#[no_mangle]
+pub unsafe extern "C" fn flecs_log_to_rust() {
+    LOG_SINK = LogSink::Rust;
+}
+
+// Last error of a thread, from flecs error messages and failed exports
+struct LastError {
+    code: i32,
+    message: std::ffi::CString,
+}
+
+thread_local! {
+    static LAST_ERROR: std::cell::RefCell<Option<LastError>> = const { std::cell::RefCell::new(None) };
+}
+
+// flecs sets the code of a failed check or abort in ecs_os_api, which all
+// threads share, and only after logging it. The message ends with the name of
+// the code though, so the code is taken from there on the thread that logs it.
+unsafe fn error_record(level: i32, msg: *const c_char) {
+    let message = std::ffi::CStr::from_ptr(msg).to_owned();
+    let code = if level <= -4 { error_code(message.to_bytes()) } else { ECS_OPERATION_FAILED as i32 };
+    LAST_ERROR.with_borrow_mut(|error| *error = Some(LastError { code, message }));
+}
+
+// Code of a fatal message such as "assert: ... (INVALID_PARAMETER)"
+unsafe fn error_code(message: &[u8]) -> i32 {
+    let message = message.strip_suffix(b")").unwrap_or(message);
+    (1..=ECS_INVALID_FROM_WORKER as i32)
+        .find(|code| {
+            let name = std::ffi::CStr::from_ptr(ecs_strerror(*code)).to_bytes();
+            // A whole word, so OUT_OF_RANGE doesn't match COLUMN_INDEX_OUT_OF_RANGE
+            match message.strip_suffix(name) {
+                Some(rest) if name != b"unknown error code" => {
+                    rest.is_empty() || rest.ends_with(b" ") || rest.ends_with(b"(")
+                }
+                _ => false,
+            }
+        })
+        .unwrap_or(ECS_OPERATION_FAILED as i32)
+}
+
+// Records an error of an export that fails without flecs logging one
+fn error_set(code: u32, message: &str) {
+    // Messages can hold names from callers, which may contain NULs
+    let message = std::ffi::CString::new(message.replace('\0', "\u{FFFD}")).unwrap_or_default();
+    LAST_ERROR.with_borrow_mut(|error| *error = Some(LastError { code: code as i32, message }));
+}
+
+// Called with the error code and message when flecs hits an error it can't
+// recover from. Returns whether the hook replaces the abort.
+pub type flecs_abort_callback_t = unsafe extern "C" fn(code: i32, message: *const c_char) -> bool;
+
+static mut ABORT_HOOK: Option<flecs_abort_callback_t> = None;
+
+unsafe extern "C" fn abort_trampoline() {
+    let code = flecs_last_error();
+    let replaced = match ABORT_HOOK {
+        Some(hook) => hook(code, flecs_last_error_message()),
+        None => false,
+    };
+    if replaced {
+        #[cfg(target_arch = "wasm32")]
+        core::arch::wasm32::unreachable();
+        #[cfg(not(target_arch = "wasm32"))]
+        std::process::exit(if code == 0 { ECS_INTERNAL_ERROR as i32 } else { code });
+    }
+    std::process::abort();
+}
+
+// Sets the hook that is called when flecs would abort, or none if hook is
+// null. flecs can't continue the call that hit the error, so the hook chooses
+// how it ends: returning false aborts the process or wasm instance, returning
+// true replaces the abort. On wasm the instance then traps, which the host can
+// catch as an exception and keep the instance for, e.g. to read the last error
+// or to save state. Natively the process exits with the error code, without
+// the abort signal or a core dump.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_set_abort_hook(hook: Option<flecs_abort_callback_t>) {
+    ABORT_HOOK = hook;
+}
+
+// Code of the last error on this thread, such as ECS_INVALID_PARAMETER, or 0
+// if there was none since flecs_clear_error. Errors flecs logs without a code
+// are ECS_OPERATION_FAILED.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_last_error() -> i32 {
+    LAST_ERROR.with_borrow(|error| error.as_ref().map_or(0, |error| error.code))
+}
+
+// Message of the last error on this thread, or null. Valid until the next
+// error or flecs_clear_error.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_last_error_message() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| match error {
+        Some(error) => error.message.as_ptr(),
+        None => std::ptr::null(),
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_clear_error() {
+    LAST_ERROR.with_borrow_mut(|error| *error = None);
+}
+
+static WORLD_CREATED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+// Allocation functions flecs uses instead of malloc, realloc, calloc and free.
+// Memory that exports hand out for m_free still comes from malloc.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct flecs_allocator_t {
+    pub malloc: ecs_os_api_malloc_t,
+    pub realloc: ecs_os_api_realloc_t,
+    pub calloc: ecs_os_api_calloc_t,
+    pub free: ecs_os_api_free_t,
+}
+
+static mut ALLOCATOR: Option<flecs_allocator_t> = None;
+
+// Allocator for flecs, installed with set_allocator. Blocks need the alignment
+// of malloc.
+pub trait Allocator: Sync {
+    fn malloc(&self, size: usize) -> *mut c_void;
+    fn realloc(&self, ptr: *mut c_void, size: usize) -> *mut c_void;
+    fn free(&self, ptr: *mut c_void);
+
+    fn calloc(&self, size: usize) -> *mut c_void {
+        let ptr = self.malloc(size);
+        if !ptr.is_null() {
+            unsafe { std::ptr::write_bytes(ptr as *mut u8, 0, size) };
+        }
+        ptr
+    }
+}
+
+static mut RUST_ALLOCATOR: Option<&'static dyn Allocator> = None;
+
+unsafe extern "C" fn rust_malloc(size: ecs_size_t) -> *mut c_void {
+    RUST_ALLOCATOR.unwrap().malloc(size as usize)
+}
+
+unsafe extern "C" fn rust_realloc(ptr: *mut c_void, size: ecs_size_t) -> *mut c_void {
+    RUST_ALLOCATOR.unwrap().realloc(ptr, size as usize)
+}
+
+unsafe extern "C" fn rust_calloc(size: ecs_size_t) -> *mut c_void {
+    RUST_ALLOCATOR.unwrap().calloc(size as usize)
+}
+
+unsafe extern "C" fn rust_free(ptr: *mut c_void) {
+    RUST_ALLOCATOR.unwrap().free(ptr)
+}
+
+// Installs the allocator flecs uses, or the default if allocator is null.
+// Returns false if the world already exists or a function is missing.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_set_allocator(allocator: *const flecs_allocator_t) -> bool {
+    if WORLD_CREATED.load(std::sync::atomic::Ordering::Relaxed) {
+        error_set(ECS_INVALID_OPERATION, "the allocator can't change once the world exists");
+        return false;
+    }
+    if allocator.is_null() {
+        ALLOCATOR = None;
+        return true;
+    }
+    let allocator = *allocator;
+    if allocator.malloc.is_none() || allocator.realloc.is_none() || allocator.calloc.is_none() || allocator.free.is_none() {
+        error_set(ECS_INVALID_PARAMETER, "allocator is missing a function");
+        return false;
+    }
+    ALLOCATOR = Some(allocator);
+    true
+}
+
+// Installs a Rust allocator, see flecs_set_allocator
+pub fn set_allocator(allocator: &'static dyn Allocator) -> bool {
+    unsafe {
+        let installed = flecs_set_allocator(&flecs_allocator_t {
+            malloc: Some(rust_malloc),
+            realloc: Some(rust_realloc),
+            calloc: Some(rust_calloc),
+            free: Some(rust_free),
+        });
+        if installed {
+            RUST_ALLOCATOR = Some(allocator);
+        }
+        installed
+    }
+}
+
+// Memory flecs has allocated, and what it allocated during the last frame
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct flecs_memory_stats_t {
+    pub live_bytes: i64,
+    pub live_count: i64,
+    // Calls to malloc, realloc and calloc, and the bytes they requested
+    pub frame_alloc_count: i64,
+    pub frame_alloc_bytes: i64,
+    pub frame_free_count: i64,
+}
+
+// The tracking allocator wraps the installed one, and stores the size of each
+// block in a header that keeps the alignment of malloc
+const TRACKING_HEADER: usize = 16;
+static TRACKING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+static mut TRACKED: Option<flecs_allocator_t> = None;
+
+struct MemoryCounters {
+    live_bytes: std::sync::atomic::AtomicI64,
+    live_count: std::sync::atomic::AtomicI64,
+    alloc_count: std::sync::atomic::AtomicI64,
+    alloc_bytes: std::sync::atomic::AtomicI64,
+    free_count: std::sync::atomic::AtomicI64,
+}
+
+impl MemoryCounters {
+    const fn new() -> Self {
+        MemoryCounters {
+            live_bytes: std::sync::atomic::AtomicI64::new(0),
+            live_count: std::sync::atomic::AtomicI64::new(0),
+            alloc_count: std::sync::atomic::AtomicI64::new(0),
+            alloc_bytes: std::sync::atomic::AtomicI64::new(0),
+            free_count: std::sync::atomic::AtomicI64::new(0),
+        }
+    }
+}
+
+// Counters of the current frame, and the totals of the last one
+static MEMORY: MemoryCounters = MemoryCounters::new();
+static MEMORY_FRAME: MemoryCounters = MemoryCounters::new();
+
+fn memory_count(counter: &std::sync::atomic::AtomicI64, value: i64) {
+    counter.fetch_add(value, std::sync::atomic::Ordering::Relaxed);
+}
+
+// Writes the size header and returns the block after it
+unsafe fn tracking_block(block: *mut c_void, size: usize) -> *mut c_void {
+    if block.is_null() {
+        return block;
+    }
+    *(block as *mut usize) = size;
+    (block as *mut u8).add(TRACKING_HEADER) as *mut c_void
+}
+
+unsafe fn tracking_header(ptr: *mut c_void) -> (*mut c_void, usize) {
+    let block = (ptr as *mut u8).sub(TRACKING_HEADER) as *mut c_void;
+    (block, *(block as *const usize))
+}
+
+unsafe fn tracking_alloc(size: usize) {
+    memory_count(&MEMORY.live_bytes, size as i64);
+    memory_count(&MEMORY.live_count, 1);
+    memory_count(&MEMORY.alloc_count, 1);
+    memory_count(&MEMORY.alloc_bytes, size as i64);
+}
+
+unsafe extern "C" fn tracking_malloc(size: ecs_size_t) -> *mut c_void {
+    let block = TRACKED.unwrap().malloc.unwrap()(size + TRACKING_HEADER as ecs_size_t);
+    if !block.is_null() {
+        tracking_alloc(size as usize);
+    }
+    tracking_block(block, size as usize)
+}
+
+unsafe extern "C" fn tracking_calloc(size: ecs_size_t) -> *mut c_void {
+    let block = TRACKED.unwrap().calloc.unwrap()(size + TRACKING_HEADER as ecs_size_t);
+    if !block.is_null() {
+        tracking_alloc(size as usize);
+    }
+    tracking_block(block, size as usize)
+}
+
+unsafe extern "C" fn tracking_realloc(ptr: *mut c_void, size: ecs_size_t) -> *mut c_void {
+    if ptr.is_null() {
+        return tracking_malloc(size);
+    }
+    let (block, previous_size) = tracking_header(ptr);
+    let block = TRACKED.unwrap().realloc.unwrap()(block, size + TRACKING_HEADER as ecs_size_t);
+    if block.is_null() {
+        return block;
+    }
+    memory_count(&MEMORY.live_bytes, size as i64 - previous_size as i64);
+    memory_count(&MEMORY.alloc_count, 1);
+    memory_count(&MEMORY.alloc_bytes, size as i64);
+    tracking_block(block, size as usize)
+}
+
+unsafe extern "C" fn tracking_free(ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+    let (block, size) = tracking_header(ptr);
+    memory_count(&MEMORY.live_bytes, -(size as i64));
+    memory_count(&MEMORY.live_count, -1);
+    memory_count(&MEMORY.free_count, 1);
+    TRACKED.unwrap().free.unwrap()(block)
+}
+
+unsafe fn allocator_install(api: &mut ecs_os_api_t) {
+    if let Some(allocator) = ALLOCATOR {
+        api.malloc_ = allocator.malloc;
+        api.realloc_ = allocator.realloc;
+        api.calloc_ = allocator.calloc;
+        api.free_ = allocator.free;
+    }
+    if TRACKING.load(std::sync::atomic::Ordering::Relaxed) {
+        TRACKED = Some(flecs_allocator_t { malloc: api.malloc_, realloc: api.realloc_, calloc: api.calloc_, free: api.free_ });
+        api.malloc_ = Some(tracking_malloc);
+        api.realloc_ = Some(tracking_realloc);
+        api.calloc_ = Some(tracking_calloc);
+        api.free_ = Some(tracking_free);
+    }
+}
+
+// Ends the frame of the per-frame counters
+fn memory_frame() {
+    use std::sync::atomic::Ordering::Relaxed;
+    MEMORY_FRAME.alloc_count.store(MEMORY.alloc_count.swap(0, Relaxed), Relaxed);
+    MEMORY_FRAME.alloc_bytes.store(MEMORY.alloc_bytes.swap(0, Relaxed), Relaxed);
+    MEMORY_FRAME.free_count.store(MEMORY.free_count.swap(0, Relaxed), Relaxed);
+}
+
+// Tracks the memory flecs allocates, on top of the installed allocator.
+// Returns false if the world already exists.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_set_memory_tracking(enabled: bool) -> bool {
+    if WORLD_CREATED.load(std::sync::atomic::Ordering::Relaxed) {
+        error_set(ECS_INVALID_OPERATION, "memory tracking can't change once the world exists");
+        return false;
+    }
+    TRACKING.store(enabled, std::sync::atomic::Ordering::Relaxed);
+    true
+}
+
+// Frames end with flecs_progress. Returns false if memory isn't tracked.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_memory_stats(stats: *mut flecs_memory_stats_t) -> bool {
+    use std::sync::atomic::Ordering::Relaxed;
+    if !TRACKING.load(Relaxed) {
+        *stats = flecs_memory_stats_t::default();
+        return false;
+    }
+    *stats = flecs_memory_stats_t {
+        live_bytes: MEMORY.live_bytes.load(Relaxed),
+        live_count: MEMORY.live_count.load(Relaxed),
+        frame_alloc_count: MEMORY_FRAME.alloc_count.load(Relaxed),
+        frame_alloc_bytes: MEMORY_FRAME.alloc_bytes.load(Relaxed),
+        frame_free_count: MEMORY_FRAME.free_count.load(Relaxed),
+    };
+    true
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_make_pair(relation: ecs_entity_t, object: ecs_entity_t) -> ecs_entity_t {
+    let relation: ecs_entity_t = relation;
+    let object: ecs_entity_t = object;
+    ecs_make_pair(relation, object)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_filter_children_init(id: ecs_entity_t) -> *mut ecs_filter_t {
+    let world = *WORLD;
+    let mut desc: ecs_filter_desc_t = MaybeUninit::zeroed().assume_init();
+    desc.terms[0].id = ecs_make_pair(EcsChildOf, id);
+    desc.terms[1].id = EcsPrefab;
+    desc.terms[1].oper = ecs_oper_kind_t_EcsOptional;
+    ecs_filter_init(world, &desc)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_filter_iter(filter: *mut ecs_filter_t) -> *mut ecs_iter_t {
+    let world = *WORLD;
+    if filter.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "filter is null");
+        return std::ptr::null_mut();
+    }
+    let it = ecs_filter_iter(world, filter);
+    Box::into_raw(Box::new(it))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_iter_entities(iter: *mut ecs_iter_t, count: *mut i32) -> *const ecs_entity_t {
+    *count = (*iter).count;
+    if (*iter).count > 0 {
+        (*iter).entities
+    } else {
+        std::ptr::null()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_delete_entity(entity: ecs_entity_t) {
+    let world = *WORLD;
+    let entity: ecs_entity_t = entity;
+    ecs_delete(world, entity);
+}
+
+// Deletes every entity with the id, which may be a pair or wildcard.
+// Unloading a level is flecs_delete_with(flecs_make_pair(EcsChildOf, level)).
+#[no_mangle]
+pub unsafe extern "C" fn flecs_delete_with(id: ecs_id_t) {
+    let world = *WORLD;
+    ecs_delete_with(world, id);
+}
+
+// Removes the id from every entity that has it, without deleting the entities
+#[no_mangle]
+pub unsafe extern "C" fn flecs_remove_all(id: ecs_id_t) {
+    let world = *WORLD;
+    ecs_remove_all(world, id);
+}
+
+// Compacts memory after large unloads. Tables that stayed empty for more than
+// clear_generation calls release their storage, and tables empty for more than
+// delete_generation calls are deleted. A non-zero id limits the cleanup to
+// tables with that id. Returns the number of deleted tables.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_delete_empty_tables(
+    id: ecs_id_t,
+    clear_generation: u16,
+    delete_generation: u16,
+    min_id_count: i32,
+    time_budget_seconds: f64,
+) -> i32 {
+    let world = *WORLD;
+    ecs_delete_empty_tables(world, id, clear_generation, delete_generation, min_id_count, time_budget_seconds)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_has_component(entity: ecs_entity_t, component: ecs_entity_t) -> bool {
+    let world = *WORLD;
+    ecs_has_id(world, entity, component)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_is_valid(entity: ecs_entity_t) -> bool {
+    let world = *WORLD;
+    let entity: ecs_entity_t = entity;
+    ecs_is_valid(world, entity)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn m_free(ptr: *mut c_void) {
+    free(ptr)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_component_set_member_ptr(
+    component_ptr: *mut c_void,
+    offset: u32,
+    value: *mut c_void,
+) {
+    let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut c_void;
+    *member_ptr = value;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_component_get_member_ptr(
+    component_ptr: *mut c_void,
+    offset: u32,
+) -> *mut c_void {
+    let member_ptr = (component_ptr as *mut u8).add(offset as usize) as *mut *mut c_void;
+    *member_ptr
+}
+
+// Callback for systems and observers, called with the iterator of the current
+// run and the context pointer that was registered alongside it
+pub type flecs_iter_callback_t = unsafe extern "C" fn(iter: *mut ecs_iter_t, ctx: *mut c_void);
+
+// Host function that runs a guest callback known only by its index in the
+// wasm function table, for guests that can't hand out native function pointers
+pub type flecs_callback_dispatcher_t =
+    unsafe extern "C" fn(callback_index: u32, iter: *mut ecs_iter_t, ctx: *mut c_void);
+
+static mut CALLBACK_DISPATCHER: Option<flecs_callback_dispatcher_t> = None;
+
+// What a system or observer calls back into. Stored boxed in binding_ctx and
+// dropped by callback_free once the system or observer is deleted.
+pub enum Callback {
+    Function { callback: flecs_iter_callback_t, ctx: *mut c_void },
+    Indexed { callback_index: u32, ctx: *mut c_void },
+    Closure(Box<dyn FnMut(&mut Iter) + Send>),
+}
+
+// Iterator passed to Rust closures registered as systems or observers
+pub struct Iter {
+    iter: *mut ecs_iter_t,
+}
+
+impl Iter {
+    /// # Safety
+    /// iter has to point to an iterator that stays valid while the Iter is used
+    pub unsafe fn from_raw(iter: *mut ecs_iter_t) -> Self {
+        Iter { iter }
+    }
+
+    pub fn as_ptr(&self) -> *mut ecs_iter_t {
+        self.iter
+    }
+
+    pub fn count(&self) -> usize {
+        unsafe { (*self.iter).count as usize }
+    }
+
+    pub fn entities(&self) -> &[ecs_entity_t] {
+        unsafe {
+            if (*self.iter).entities.is_null() {
+                return &[];
+            }
+            std::slice::from_raw_parts((*self.iter).entities, self.count())
+        }
+    }
+
+    pub fn delta_time(&self) -> f32 {
+        unsafe { (*self.iter).delta_time }
+    }
+
+    // Event that triggered an observer, 0 for systems
+    pub fn event(&self) -> ecs_entity_t {
+        unsafe { (*self.iter).event }
+    }
+
+    pub fn field_is_set(&self, term_index: i32) -> bool {
+        unsafe { ecs_field_is_set(self.iter, term_index) }
+    }
+
+    pub fn field_is_self(&self, term_index: i32) -> bool {
+        unsafe { ecs_field_is_self(self.iter, term_index) }
+    }
+
+    /// Component data of a field, one element per entity or a single element
+    /// when the field is shared. None when an optional field didn't match.
+    ///
+    /// # Safety
+    /// T has to be the type of the field's component, and the same field must
+    /// not be borrowed twice at once.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn field<T>(&self, term_index: i32) -> Option<&mut [T]> {
+        if !self.field_is_set(term_index) {
+            return None;
+        }
+        let field = ecs_field_w_size(self.iter, std::mem::size_of::<T>(), term_index) as *mut T;
+        if field.is_null() {
+            return None;
+        }
+        let len = if self.field_is_self(term_index) { self.count() } else { 1 };
+        Some(std::slice::from_raw_parts_mut(field, len))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Event {
+    OnAdd,
+    OnRemove,
+    OnSet,
+    UnSet,
+}
+
+unsafe fn get_event(event: u8) -> ecs_entity_t {
+    match event {
+        0 => EcsOnAdd,
+        1 => EcsOnRemove,
+        2 => EcsOnSet,
+        _ => EcsUnSet,
+    }
+}
+
+unsafe extern "C" fn callback_free(binding_ctx: *mut c_void) {
+    drop(Box::from_raw(binding_ctx as *mut Callback));
+}
+
+#[no_mangle]
+// Trampoline from the flecs C callback to the Callback stored in binding_ctx
+pub unsafe extern "C" fn query_trampoline(iter: *mut ecs_iter_t) {
+    // println!("This system runs on this thread from trampoline: {}", std::thread::ThreadId::as_u64(&std::thread::current().id()));
+    // println!("Pthread ID from trampoline: {}", pthread_self());
+    let callback = (*iter).binding_ctx as *mut Callback;
+    if callback.is_null() {
+        return;
+    }
+    match &mut *callback {
+        Callback::Function { callback, ctx } => callback(iter, *ctx),
+        Callback::Indexed { callback_index, ctx } => {
+            if let Some(dispatcher) = CALLBACK_DISPATCHER {
+                dispatcher(*callback_index, iter, *ctx);
+            }
+        }
+        Callback::Closure(closure) => closure(&mut Iter::from_raw(iter)),
+    }
+}
+
+// Registers the host function that runs callbacks created with
+// flecs_system_create_indexed and flecs_observer_create_indexed
+#[no_mangle]
+pub unsafe extern "C" fn flecs_set_callback_dispatcher(dispatcher: Option<flecs_callback_dispatcher_t>) {
+    CALLBACK_DISPATCHER = dispatcher;
+}
+
+unsafe fn system_desc_with_callback(callback: Callback) -> *mut ecs_system_desc_t {
+    let mut system_desc: ecs_system_desc_t = MaybeUninit::zeroed().assume_init();
+    system_desc.binding_ctx = Box::into_raw(Box::new(callback)) as *mut c_void;
+    system_desc.binding_ctx_free = Some(callback_free);
+    system_desc.callback = Some(query_trampoline);
+    #[cfg(feature = "multithread")] {
+        system_desc.multi_threaded = true;
+    }
+    Box::into_raw(Box::new(system_desc))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_system_create(
+    callback: flecs_iter_callback_t,
+    ctx: *mut c_void,
+) -> *mut ecs_system_desc_t {
+    system_desc_with_callback(Callback::Function { callback, ctx })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_system_create_indexed(
+    callback_index: u32,
+    ctx: *mut c_void,
+) -> *mut ecs_system_desc_t {
+    system_desc_with_callback(Callback::Indexed { callback_index, ctx })
+}
+
+// System that runs a Rust closure. The closure is dropped together with the
+// system entity.
+pub fn system_create_closure<F>(closure: F) -> *mut ecs_system_desc_t
+where
+    F: FnMut(&mut Iter) + Send + 'static,
+{
+    unsafe { system_desc_with_callback(Callback::Closure(Box::new(closure))) }
+}
+
+// System that calls a plain flecs iter action, without a context
+#[no_mangle]
+pub unsafe extern "C" fn flecs_system_create_hook(hook: ecs_iter_action_t) -> *mut ecs_system_desc_t {
+    let mut system_desc: ecs_system_desc_t = MaybeUninit::zeroed().assume_init();
+    system_desc.callback = hook;
+    #[cfg(feature = "multithread")] {
+        system_desc.multi_threaded = true;
+    }
+    Box::into_raw(Box::new(system_desc))
+}
+
+// Iterates like flecs does for systems without a run action, so that a trace
+// recording can time the whole run instead of each table
+unsafe extern "C" fn system_run(iter: *mut ecs_iter_t) {
+    let tracing = TRACING.load(std::sync::atomic::Ordering::Relaxed);
+    let start = if tracing { trace_now() } else { 0 };
+    let callback = (*iter).callback.unwrap();
+    if (*iter).field_count == 0 {
+        callback(iter);
+        ecs_iter_fini(iter);
+    } else {
+        while ecs_iter_next(iter) {
+            callback(iter);
+        }
+    }
+    if tracing {
+        trace_system(iter, start, trace_now());
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_system_build(
+    system_desc: *mut ecs_system_desc_t,
+) -> ecs_entity_t {
+    let world = *WORLD;
+    let mut entity_desc: ecs_entity_desc_t = unsafe { MaybeUninit::zeroed().assume_init() };
+    // We have to add this pair so that the system is part of standard progress stage
+    entity_desc.add[0] = unsafe { ecs_make_pair(EcsDependsOn, EcsOnUpdate) };
+    (*system_desc).entity = ecs_entity_init(world, &entity_desc);
+    if (*system_desc).run.is_none() {
+        (*system_desc).run = Some(system_run);
+    }
+    ecs_system_init(world, system_desc)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_query_from_system_desc(
+    system_desc: *mut ecs_system_desc_t
+) -> *mut ecs_query_desc_t {
+    &mut (*system_desc).query as *mut ecs_query_desc_t
+}
+
+unsafe fn observer_desc_with_callback(callback: Callback) -> *mut ecs_observer_desc_t {
+    let mut observer_desc: ecs_observer_desc_t = MaybeUninit::zeroed().assume_init();
+    observer_desc.binding_ctx = Box::into_raw(Box::new(callback)) as *mut c_void;
+    observer_desc.binding_ctx_free = Some(callback_free);
+    observer_desc.callback = Some(query_trampoline);
+    Box::into_raw(Box::new(observer_desc))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_observer_create(
+    callback: flecs_iter_callback_t,
+    ctx: *mut c_void,
+) -> *mut ecs_observer_desc_t {
+    observer_desc_with_callback(Callback::Function { callback, ctx })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_observer_create_indexed(
+    callback_index: u32,
+    ctx: *mut c_void,
+) -> *mut ecs_observer_desc_t {
+    observer_desc_with_callback(Callback::Indexed { callback_index, ctx })
+}
+
+// Observer that runs a Rust closure. The closure is dropped together with the
+// observer entity.
+pub fn observer_create_closure<F>(closure: F) -> *mut ecs_observer_desc_t
+where
+    F: FnMut(&mut Iter) + Send + 'static,
+{
+    unsafe { observer_desc_with_callback(Callback::Closure(Box::new(closure))) }
+}
+
+// Observer that calls a plain flecs iter action, without a context
+#[no_mangle]
+pub unsafe extern "C" fn flecs_observer_create_hook(hook: ecs_iter_action_t) -> *mut ecs_observer_desc_t {
+    let mut observer_desc: ecs_observer_desc_t = MaybeUninit::zeroed().assume_init();
+    observer_desc.callback = hook;
+    Box::into_raw(Box::new(observer_desc))
+}
+
+// Adds one of the Event kinds to the observer. Returns false when all event
+// slots are taken.
+#[no_mangle]
+pub unsafe extern "C" fn flecs_observer_add_event(observer_desc: *mut ecs_observer_desc_t, event: u8) -> bool {
+    let events = &mut (*observer_desc).events;
+    match events.iter_mut().find(|slot| **slot == 0) {
+        Some(slot) => {
+            *slot = get_event(event);
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_filter_from_observer_desc(
+    observer_desc: *mut ecs_observer_desc_t
+) -> *mut ecs_filter_desc_t {
+    &mut (*observer_desc).filter as *mut ecs_filter_desc_t
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_observer_build(observer_desc: *mut ecs_observer_desc_t) -> ecs_entity_t {
+    let world = *WORLD;
+    ecs_observer_init(world, observer_desc)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_component_lookup(name: *mut c_char) -> ecs_entity_t {
+    let world = *WORLD;
+    let component_id: ecs_entity_t = ecs_lookup(world, name);
+    component_id
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn flecs_entity_to_json(entity: ecs_entity_t) -> *mut c_char {
+    let world = *WORLD;
+    let mut json_desc: ecs_entity_to_json_desc_t = unsafe { MaybeUninit::zeroed().assume_init() };
+    json_desc.serialize_base = true;
+    json_desc.serialize_ids = true;
     json_desc.serialize_values = true;
     json_desc.serialize_refs = EcsChildOf;
     json_desc.serialize_path = true;
@@ -1059,11 +4067,15 @@ pub unsafe fn flecs_entity_to_json(entity: ecs_entity_t) -> *mut c_char {
     // json_desc.serialize_matches = true;
     // json_desc.serialize_private = true;
     let json: *mut c_char = ecs_entity_to_json(world, entity, &json_desc);
-    json
+    if json.is_null() {
+        error_set(ECS_INVALID_PARAMETER, "entity can't be serialized");
+        return std::ptr::null_mut();
+    }
+    os_string_to_malloc(json)
 }
 
 #[no_mangle]
-pub unsafe fn flecs_json_to_entity(json: *mut c_char) {
+pub unsafe extern "C" fn flecs_json_to_entity(json: *mut c_char) {
     let world = *WORLD;
     // let mut json_desc: ecs_from_json_desc_t = unsafe { MaybeUninit::zeroed().assume_init() };
     // json_desc.strict = false;
@@ -1073,14 +4085,17 @@ pub unsafe fn flecs_json_to_entity(json: *mut c_char) {
     // let entity = toxoid_api::Entity::from_id(entity as u64);
     // let result = ecs_entity_from_json(world, entity.get_id(), json, std::ptr::null());
     // println!("Result: {}", *result);
-    
     // // println!("Entity has Position? {}", entity.has::<toxoid_api::components::Position>());
     // let position = entity.get::<toxoid_api::components::Position>();
     // println!("x: {}, y: {}", position.get_x(), position.get_y());
 }
 
 #[no_mangle]
-pub unsafe fn flecs_entity_set_name(entity: ecs_entity_t, name: *mut c_char) {
+pub unsafe extern "C" fn flecs_entity_set_name(entity: ecs_entity_t, name: *mut c_char) {
     let world = *WORLD;
+    if !ecs_is_alive(world, entity) {
+        error_set(ECS_INVALID_PARAMETER, "entity is not alive");
+        return;
+    }
     ecs_set_name(world, entity, name);
 }
\ No newline at end of file
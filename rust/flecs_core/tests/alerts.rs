@@ -0,0 +1,63 @@
+// Alerts on entities matching a query
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::CStr;
+
+#[test]
+fn alerts_report_matching_entities() {
+    let _lock = lock();
+    unsafe {
+        let health = component("AlertsHealth", &[("value", Type::F32 as u8)]);
+        let position = component("AlertsPosition", &[("x", Type::F32 as u8), ("y", Type::F32 as u8)]);
+        let broken = flecs_entity_create_named(c"broken".as_ptr());
+        flecs_entity_add_component(broken, health);
+        let fine = flecs_entity_create();
+        flecs_entity_add_component(fine, health);
+        flecs_entity_add_component(fine, position);
+
+        let alert = flecs_alert_create(
+            c"alerts.missing_position".as_ptr(),
+            c"AlertsHealth, !AlertsPosition".as_ptr(),
+            c"$this has Health but no Position".as_ptr(),
+            AlertSeverity::Warning as u8,
+            0.0,
+        );
+        assert_ne!(alert, 0);
+        assert_eq!(
+            flecs_alert_create(std::ptr::null(), c"AlertsMissing".as_ptr(), std::ptr::null(), 0, 0.0),
+            0
+        );
+        assert_eq!(
+            flecs_alert_create(std::ptr::null(), c"AlertsHealth".as_ptr(), std::ptr::null(), 42, 0.0),
+            0
+        );
+
+        // Alerts are evaluated every half second
+        flecs_progress(1.0);
+        flecs_progress(1.0);
+        assert_eq!(flecs_entity_alert_count(broken, 0), 1);
+        assert_eq!(flecs_entity_alert_count(broken, alert), 1);
+        assert_eq!(flecs_entity_alert_count(fine, 0), 0);
+
+        let mut count = 0;
+        let instances = flecs_alerts_active(0, &mut count);
+        assert_eq!(count, 1);
+        let instance = *instances;
+        assert_eq!(instance.alert, alert);
+        assert_eq!(instance.entity, broken);
+        assert_eq!(instance.severity, AlertSeverity::Warning as u8);
+        assert_eq!(CStr::from_ptr(instance.message).to_str().unwrap(), "broken has Health but no Position");
+        m_free(instances as *mut _);
+
+        // Without a retain period the alert clears once the entity matches
+        flecs_entity_add_component(broken, position);
+        flecs_progress(1.0);
+        flecs_progress(1.0);
+        assert_eq!(flecs_entity_alert_count(broken, 0), 0);
+        assert!(flecs_alerts_active(alert, &mut count).is_null());
+        assert_eq!(count, 0);
+    }
+}
@@ -0,0 +1,84 @@
+// Custom and tracking allocators for the memory flecs allocates
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{c_void, CStr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator {
+    calls: AtomicUsize,
+}
+
+impl Allocator for CountingAllocator {
+    fn malloc(&self, size: usize) -> *mut c_void {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        unsafe { malloc(size) }
+    }
+
+    fn realloc(&self, ptr: *mut c_void, size: usize) -> *mut c_void {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        unsafe { realloc(ptr as *mut _, size as u64) }
+    }
+
+    fn free(&self, ptr: *mut c_void) {
+        unsafe { free(ptr) }
+    }
+}
+
+static ALLOCATOR: CountingAllocator = CountingAllocator { calls: AtomicUsize::new(0) };
+
+#[derive(Clone, Copy, Default)]
+#[allow(dead_code)]
+struct Payload {
+    values: [u64; 16],
+}
+
+#[test]
+fn allocations_go_through_the_installed_allocator() {
+    let _lock = lock();
+    unsafe {
+        let mut stats = flecs_memory_stats_t::default();
+        assert!(!flecs_memory_stats(&mut stats));
+
+        // Both have to be set before the world is created
+        assert!(set_allocator(&ALLOCATOR));
+        assert!(flecs_set_memory_tracking(true));
+        let payload = flecs_component_create_sized(
+            c"AllocatorPayload".as_ptr(),
+            std::mem::size_of::<Payload>() as i32,
+            std::mem::align_of::<Payload>() as i32,
+        );
+        assert!(!set_allocator(&ALLOCATOR));
+        assert!(!flecs_set_memory_tracking(false));
+        assert!(ALLOCATOR.calls.load(Ordering::Relaxed) > 0);
+
+        flecs_progress(0.0);
+        assert!(flecs_memory_stats(&mut stats));
+        let live_bytes = stats.live_bytes;
+        assert!(live_bytes > 0);
+        assert!(stats.live_count > 0);
+
+        let entities: Vec<ecs_entity_t> = (0..1000).map(|_| flecs_entity_create()).collect();
+        for entity in &entities {
+            flecs_entity_add_component(*entity, payload);
+        }
+        flecs_progress(0.0);
+        flecs_memory_stats(&mut stats);
+        assert!(stats.frame_alloc_count > 0);
+        assert!(stats.frame_alloc_bytes >= 1000 * std::mem::size_of::<Payload>() as i64);
+        assert!(stats.live_bytes >= live_bytes + stats.frame_alloc_bytes / 2, "{:?}", stats);
+
+        // Frames without allocations report none
+        flecs_progress(0.0);
+        flecs_progress(0.0);
+        flecs_memory_stats(&mut stats);
+        assert_eq!(stats.frame_alloc_count, 0, "{:?}", stats);
+
+        // Strings flecs allocates are handed out in malloc'd memory
+        let json = flecs_entity_to_json(entities[0]);
+        assert!(CStr::from_ptr(json).to_str().unwrap().contains("AllocatorPayload"));
+        m_free(json as *mut _);
+    }
+}
@@ -0,0 +1,132 @@
+// Systems and observers calling back through C function pointers with a
+// context, through a host dispatcher by callback index, and as plain hooks
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// Adds the number of iterated entities to the counter passed as ctx
+unsafe extern "C" fn count_entities(iter: *mut ecs_iter_t, ctx: *mut c_void) {
+    *(ctx as *mut usize) += flecs_iter_count(iter) as usize;
+}
+
+static DISPATCHED: Mutex<Vec<(u32, ecs_entity_t)>> = Mutex::new(Vec::new());
+
+unsafe extern "C" fn dispatch(callback_index: u32, iter: *mut ecs_iter_t, ctx: *mut c_void) {
+    let entities = std::slice::from_raw_parts((*iter).entities, flecs_iter_count(iter) as usize);
+    let mut dispatched = DISPATCHED.lock().unwrap();
+    dispatched.extend(entities.iter().map(|entity| (callback_index, *entity)));
+    count_entities(iter, ctx);
+}
+
+static HOOK_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn hook(iter: *mut ecs_iter_t) {
+    HOOK_RUNS.fetch_add(flecs_iter_count(iter) as usize, Ordering::SeqCst);
+}
+
+unsafe fn entities_with(component: ecs_entity_t, count: usize) -> Vec<ecs_entity_t> {
+    (0..count)
+        .map(|_| {
+            let entity = flecs_entity_create();
+            flecs_entity_add_component(entity, component);
+            entity
+        })
+        .collect()
+}
+
+#[test]
+fn callbacks_receive_their_context() {
+    let _lock = lock();
+    unsafe {
+        let marker = component("CallbacksContextMarker", &[("value", Type::U32 as u8)]);
+        entities_with(marker, 3);
+
+        let mut system_count = 0usize;
+        let system_desc = flecs_system_create(count_entities, &mut system_count as *mut usize as *mut c_void);
+        let mut ids = [marker];
+        flecs_query_with(flecs_query_from_system_desc(system_desc), 0, ids.as_mut_ptr(), 1);
+        let system = flecs_system_build(system_desc);
+
+        let mut observer_count = 0usize;
+        let observer_desc = flecs_observer_create(count_entities, &mut observer_count as *mut usize as *mut c_void);
+        assert!(flecs_observer_add_event(observer_desc, Event::OnAdd as u8));
+        flecs_filter_with(flecs_filter_from_observer_desc(observer_desc), 0, ids.as_mut_ptr(), 1);
+        let observer = flecs_observer_build(observer_desc);
+
+        flecs_progress(0.0);
+        flecs_progress(0.0);
+        assert_eq!(system_count, 6);
+        entities_with(marker, 2);
+        assert_eq!(observer_count, 2);
+
+        flecs_delete_entity(system);
+        flecs_delete_entity(observer);
+    }
+}
+
+#[test]
+fn indexed_callbacks_go_through_the_dispatcher() {
+    let _lock = lock();
+    unsafe {
+        let marker = component("CallbacksIndexedMarker", &[("value", Type::U32 as u8)]);
+        let entities = entities_with(marker, 2);
+        flecs_set_callback_dispatcher(Some(dispatch));
+
+        let mut count = 0usize;
+        let ctx = &mut count as *mut usize as *mut c_void;
+        let system_desc = flecs_system_create_indexed(7, ctx);
+        let mut ids = [marker];
+        flecs_query_with(flecs_query_from_system_desc(system_desc), 0, ids.as_mut_ptr(), 1);
+        let system = flecs_system_build(system_desc);
+        let observer_desc = flecs_observer_create_indexed(8, ctx);
+        assert!(flecs_observer_add_event(observer_desc, Event::OnAdd as u8));
+        flecs_filter_with(flecs_filter_from_observer_desc(observer_desc), 0, ids.as_mut_ptr(), 1);
+        let observer = flecs_observer_build(observer_desc);
+
+        flecs_progress(0.0);
+        let added = entities_with(marker, 1)[0];
+        let mut expected: Vec<_> = entities.iter().map(|entity| (7, *entity)).collect();
+        expected.push((8, added));
+        assert_eq!(*DISPATCHED.lock().unwrap(), expected);
+        assert_eq!(count, 3);
+
+        // Without a dispatcher indexed callbacks don't run
+        flecs_set_callback_dispatcher(None);
+        flecs_progress(0.0);
+        assert_eq!(count, 3);
+
+        flecs_delete_entity(system);
+        flecs_delete_entity(observer);
+    }
+}
+
+#[test]
+fn hooks_run_without_a_context() {
+    let _lock = lock();
+    unsafe {
+        let marker = component("CallbacksHookMarker", &[("value", Type::U32 as u8)]);
+        entities_with(marker, 4);
+        HOOK_RUNS.store(0, Ordering::SeqCst);
+
+        let system_desc = flecs_system_create_hook(Some(hook));
+        let mut ids = [marker];
+        flecs_query_with(flecs_query_from_system_desc(system_desc), 0, ids.as_mut_ptr(), 1);
+        let system = flecs_system_build(system_desc);
+        flecs_progress(0.0);
+        assert_eq!(HOOK_RUNS.load(Ordering::SeqCst), 4);
+        flecs_delete_entity(system);
+
+        let observer_desc = flecs_observer_create_hook(Some(hook));
+        assert!(flecs_observer_add_event(observer_desc, Event::OnAdd as u8));
+        flecs_filter_with(flecs_filter_from_observer_desc(observer_desc), 0, ids.as_mut_ptr(), 1);
+        let observer = flecs_observer_build(observer_desc);
+        entities_with(marker, 1);
+        assert_eq!(HOOK_RUNS.load(Ordering::SeqCst), 5);
+        flecs_delete_entity(observer);
+    }
+}
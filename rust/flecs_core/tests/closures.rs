@@ -0,0 +1,82 @@
+// Rust closures registered as systems and observers keep their captured state
+// between runs and are dropped when their entity is deleted
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Counts how often the closure owning it was dropped
+struct DropGuard(Arc<AtomicUsize>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn system_closure_keeps_state_and_is_dropped() {
+    let _lock = lock();
+    unsafe {
+        let counter = component("ClosureCounter", &[("value", Type::U64 as u8)]);
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, counter);
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let guard = DropGuard(drops.clone());
+        let mut runs = 0;
+        let system_desc = system_create_closure(move |iter| {
+            let _ = &guard;
+            runs += 1;
+            for value in iter.field::<u64>(1).unwrap() {
+                *value = runs;
+            }
+        });
+        let mut ids = [counter];
+        flecs_query_with(flecs_query_from_system_desc(system_desc), 0, ids.as_mut_ptr(), 1);
+        let system = flecs_system_build(system_desc);
+
+        flecs_progress(0.0);
+        flecs_progress(0.0);
+        flecs_progress(0.0);
+        assert_eq!(*(flecs_entity_get_component(entity, counter) as *const u64), 3);
+        assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+        flecs_delete_entity(system);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[test]
+fn observer_closure_sees_events_and_is_dropped() {
+    let _lock = lock();
+    unsafe {
+        let marker = tag("ClosureMarker");
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let guard = DropGuard(drops.clone());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let observed = seen.clone();
+        let observer_desc = observer_create_closure(move |iter| {
+            let _ = &guard;
+            assert_eq!(iter.event(), EcsOnAdd);
+            observed.lock().unwrap().extend_from_slice(iter.entities());
+        });
+        assert!(flecs_observer_add_event(observer_desc, Event::OnAdd as u8));
+        let mut ids = [marker];
+        flecs_filter_with(flecs_filter_from_observer_desc(observer_desc), 0, ids.as_mut_ptr(), 1);
+        let observer = flecs_observer_build(observer_desc);
+
+        let first = flecs_entity_create();
+        let second = flecs_entity_create();
+        flecs_entity_add_tag(first, marker);
+        flecs_entity_add_tag(second, marker);
+        assert_eq!(*seen.lock().unwrap(), vec![first, second]);
+
+        flecs_delete_entity(observer);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+}
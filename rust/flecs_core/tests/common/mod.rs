@@ -0,0 +1,56 @@
+// Fixtures shared by the integration tests. Every test binary includes this
+// module but uses only some of it.
+#![allow(dead_code)]
+
+use flecs_core::ecs::World;
+use flecs_core::*;
+use std::ffi::{c_char, CString};
+use std::net::TcpStream;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// All exports share the one global world, which isn't thread safe, and only
+// one World handle can exist at a time
+static LOCK: Mutex<()> = Mutex::new(());
+
+// Serializes the tests of a binary. A test that failed while holding the lock
+// doesn't fail the ones after it.
+pub fn lock() -> MutexGuard<'static, ()> {
+    LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+pub fn world() -> (MutexGuard<'static, ()>, World) {
+    (lock(), World::new())
+}
+
+pub unsafe fn component(name: &str, members: &[(&str, u8)]) -> ecs_entity_t {
+    let name = CString::new(name).unwrap();
+    let names: Vec<CString> = members.iter().map(|(name, _)| CString::new(*name).unwrap()).collect();
+    let member_names: Vec<*const c_char> = names.iter().map(|name| name.as_ptr()).collect();
+    let member_types: Vec<u8> = members.iter().map(|(_, member_type)| *member_type).collect();
+    flecs_component_create(
+        name.as_ptr(),
+        member_names.as_ptr(),
+        members.len() as u32,
+        member_types.as_ptr(),
+        members.len() as u32,
+    )
+}
+
+pub unsafe fn tag(name: &str) -> ecs_entity_t {
+    let name = CString::new(name).unwrap();
+    flecs_tag_create(name.as_ptr())
+}
+
+// HTTP servers bind their socket on their own thread, so a client connecting
+// right after the server started may have to retry
+pub fn connect(port: u16) -> TcpStream {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => return stream,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(5)),
+            Err(error) => panic!("can't connect to port {}: {}", port, error),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// Components registered with #[derive(Component)] carry reflection data
+
+mod common;
+
+use common::*;
+use flecs_core::ecs::*;
+use flecs_core::*;
+use std::ffi::CStr;
+
+#[derive(Component, Clone, Copy, Default, Debug, PartialEq)]
+#[repr(C)]
+struct Vec2 {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Component, Clone, Copy, Default, Debug, PartialEq)]
+#[repr(i32)]
+enum Shape {
+    #[default]
+    Circle,
+    Square = 4,
+}
+
+#[derive(Component, Clone, Copy, Default, Debug, PartialEq)]
+#[repr(C)]
+struct Body {
+    alive: bool,
+    mass: f64,
+    center: Vec2,
+    shape: Shape,
+    corners: [Vec2; 4],
+    grid: [[u8; 3]; 2],
+    owner: Entity,
+    id: u64,
+}
+
+#[derive(Component, Clone, Copy, Default)]
+struct Dynamic;
+
+fn to_json(entity: Entity) -> String {
+    unsafe {
+        let json = flecs_entity_to_json(entity.id());
+        let string = CStr::from_ptr(json).to_str().unwrap().to_string();
+        m_free(json as *mut _);
+        string
+    }
+}
+
+#[test]
+fn struct_members_are_reflected() {
+    let (_lock, mut world) = world();
+    let owner = world.entity_named("DeriveOwner");
+    let entity = world.entity();
+    world.set(entity, Body {
+        alive: true,
+        mass: 2.5,
+        center: Vec2 { x: 1.0, y: 2.0 },
+        shape: Shape::Square,
+        grid: [[1, 2, 3], [4, 5, 6]],
+        owner,
+        id: 7,
+        ..Default::default()
+    });
+    world.add::<Dynamic>(entity);
+
+    let json = to_json(entity);
+    assert!(json.contains("\"alive\":true"), "{}", json);
+    assert!(json.contains("\"mass\":2.5"), "{}", json);
+    assert!(json.contains("\"center\":{\"x\":1, \"y\":2}"), "{}", json);
+    assert!(json.contains("\"shape\":\"Square\""), "{}", json);
+    assert!(json.contains("\"grid\":[[1, 2, 3], [4, 5, 6]]"), "{}", json);
+    assert!(json.contains("\"owner\":\"DeriveOwner\""), "{}", json);
+    assert!(json.contains("Dynamic"), "{}", json);
+}
+
+#[test]
+fn derived_components_construct_with_default() {
+    let (_lock, mut world) = world();
+    let entity = world.entity();
+    world.add::<Body>(entity);
+    assert_eq!(world.get::<Body>(entity), Some(&Body::default()));
+    assert_eq!(world.get::<Body>(entity).unwrap().shape, Shape::Circle);
+}
+
+mod scoped {
+    use flecs_core::ecs::Component;
+
+    #[derive(Component, Clone, Copy, Default)]
+    #[repr(C)]
+    pub struct Marker {
+        pub value: u32,
+    }
+}
+
+#[test]
+fn components_are_named_by_their_module_path() {
+    let (_lock, mut world) = world();
+    // Set by the derive from where the type is defined
+    assert_eq!(<scoped::Marker as Component>::NAME, "derive::scoped::Marker");
+    assert_eq!(component_name::<scoped::Marker>(), "derive.scoped.Marker");
+    let marker = world.component::<scoped::Marker>();
+    // Module paths become scopes
+    unsafe {
+        let path = ecs_get_path_w_sep(*WORLD, 0, marker.id(), c".".as_ptr(), std::ptr::null());
+        assert_eq!(CStr::from_ptr(path).to_str().unwrap(), "derive.scoped.Marker");
+        ecs_os_api.free_.unwrap()(path as *mut _);
+    }
+}
@@ -0,0 +1,223 @@
+// The safe layer in flecs_core::ecs
+
+mod common;
+
+use common::*;
+use flecs_core::ecs::*;
+use flecs_core::{ecs_owns_id, EcsIsA, Event, WORLD};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+struct Velocity {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Frozen;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+struct Gravity {
+    y: f32,
+}
+
+impl Component for Position {
+    const NAME: &'static str = concat!(module_path!(), "::Position");
+}
+
+impl Component for Velocity {
+    const NAME: &'static str = concat!(module_path!(), "::Velocity");
+}
+
+impl Component for Frozen {
+    const NAME: &'static str = concat!(module_path!(), "::Frozen");
+}
+
+impl Component for Gravity {
+    const NAME: &'static str = concat!(module_path!(), "::Gravity");
+}
+
+#[test]
+fn components_are_set_and_read() {
+    let (_lock, mut world) = world();
+    let entity = world.entity_named("EcsSetAndRead");
+    assert_eq!(world.lookup("EcsSetAndRead"), Some(entity));
+    assert_eq!(world.name(entity), Some("EcsSetAndRead"));
+    assert!(world.get::<Position>(entity).is_none());
+
+    world.add::<Velocity>(entity);
+    assert_eq!(world.get::<Velocity>(entity), Some(&Velocity::default()));
+
+    world.set(entity, Position { x: 1.0, y: 2.0 });
+    assert_eq!(world.get::<Position>(entity), Some(&Position { x: 1.0, y: 2.0 }));
+    world.get_mut::<Position>(entity).unwrap().x = 5.0;
+    assert_eq!(world.get::<Position>(entity).unwrap().x, 5.0);
+
+    world.set(entity, Frozen);
+    assert!(world.has::<Frozen>(entity));
+    world.remove::<Frozen>(entity);
+    assert!(!world.has::<Frozen>(entity));
+
+    world.delete(entity);
+    assert!(!world.is_valid(entity));
+}
+
+#[test]
+fn query_iterates_typed_fields() {
+    let (_lock, mut world) = world();
+    let moving = world.entity();
+    world.set(moving, Position { x: 0.0, y: 0.0 });
+    world.set(moving, Velocity { x: 1.0, y: 2.0 });
+    let frozen = world.entity();
+    world.set(frozen, Position { x: 0.0, y: 0.0 });
+    world.set(frozen, Velocity { x: 1.0, y: 2.0 });
+    world.add::<Frozen>(frozen);
+
+    let query = world.query::<(&mut Position, &Velocity, Option<&Frozen>)>();
+    query.each(&mut world, |_, (position, velocity, frozen)| {
+        if frozen.is_none() {
+            position.x += velocity.x;
+            position.y += velocity.y;
+        }
+    });
+    assert_eq!(world.get::<Position>(moving), Some(&Position { x: 1.0, y: 2.0 }));
+    assert_eq!(world.get::<Position>(frozen), Some(&Position { x: 0.0, y: 0.0 }));
+
+    // Stopping early finishes the iterator on drop
+    let first = query.iter(&mut world).next().map(|(entity, _)| entity);
+    assert!(first == Some(moving) || first == Some(frozen));
+    assert_eq!(query.iter(&mut world).count(), 2);
+}
+
+#[test]
+fn inherited_components_are_read_in_place() {
+    let (_lock, mut world) = world();
+    let position = world.component::<Position>();
+    let prefab = world.entity();
+    world.set(prefab, Position { x: 1.0, y: 2.0 });
+    let instance = world.entity();
+    world.add_pair(instance, Entity::from_id(unsafe { EcsIsA }), prefab);
+    let owns = |entity: Entity| unsafe { ecs_owns_id(*WORLD, entity.id(), position.id()) };
+
+    // Reading doesn't give the instance a copy, which would move it to
+    // another table
+    let inherited = world.get::<Position>(instance).unwrap() as *const Position;
+    assert_eq!(inherited, world.get::<Position>(prefab).unwrap() as *const Position);
+    assert!(!owns(instance));
+
+    world.get_mut::<Position>(instance).unwrap().x = 5.0;
+    assert!(owns(instance));
+    assert_eq!(world.get::<Position>(instance), Some(&Position { x: 5.0, y: 2.0 }));
+    assert_eq!(world.get::<Position>(prefab), Some(&Position { x: 1.0, y: 2.0 }));
+}
+
+#[test]
+fn deleted_entities_have_nothing() {
+    let (_lock, mut world) = world();
+    let parent = world.entity();
+    let entity = world.entity_named("EcsDeleted");
+    world.set(entity, Position { x: 1.0, y: 2.0 });
+    world.child_of(entity, parent);
+    assert!(world.is_alive(entity));
+
+    world.delete(entity);
+    world.delete(entity);
+    assert!(!world.is_alive(entity));
+    assert!(!world.is_alive(Entity::default()));
+    assert!(!world.has::<Position>(entity));
+    assert_eq!(world.get::<Position>(entity), None);
+    assert_eq!(world.get_mut::<Position>(entity), None);
+    assert_eq!(world.name(entity), None);
+    assert_eq!(world.parent(entity), None);
+}
+
+#[test]
+#[should_panic(expected = "is not alive")]
+fn deleted_entities_cannot_change() {
+    let (_lock, mut world) = world();
+    let entity = world.entity();
+    world.delete(entity);
+    world.set(entity, Position { x: 1.0, y: 2.0 });
+}
+
+#[test]
+#[should_panic(expected = "borrowed mutably")]
+fn query_rejects_aliasing_terms() {
+    let (_lock, mut world) = world();
+    world.query::<(&mut Position, &Position)>();
+}
+
+#[test]
+fn systems_and_observers_run_closures() {
+    let (_lock, mut world) = world();
+    let entity = world.entity();
+    world.set(entity, Position::default());
+    world.set(entity, Velocity { x: 2.0, y: 0.0 });
+
+    let system = world.system::<(&mut Position, &Velocity), _>(|_, (position, velocity)| {
+        position.x += velocity.x;
+    });
+    world.progress(0.0);
+    world.progress(0.0);
+    assert_eq!(world.get::<Position>(entity).unwrap().x, 4.0);
+    system.delete(&mut world);
+    world.progress(0.0);
+    assert_eq!(world.get::<Position>(entity).unwrap().x, 4.0);
+
+    let set = Arc::new(AtomicUsize::new(0));
+    let counter = set.clone();
+    let observer = world.observer::<(&Velocity,), _>(&[Event::OnSet], move |_, (velocity,)| {
+        assert_eq!(velocity.x, 3.0);
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    world.set(entity, Velocity { x: 3.0, y: 0.0 });
+    assert_eq!(set.load(Ordering::SeqCst), 1);
+    observer.delete(&mut world);
+}
+
+#[test]
+fn relationships_between_entities() {
+    let (_lock, mut world) = world();
+    let parent = world.entity();
+    let child = world.entity();
+    world.child_of(child, parent);
+    assert_eq!(world.parent(child), Some(parent));
+
+    let likes = world.tag("EcsLikes");
+    world.add_pair(child, likes, parent);
+    assert!(world.has_pair(child, likes, parent));
+    assert_eq!(world.target(child, likes, 0), Some(parent));
+    world.remove_pair(child, likes, parent);
+    assert_eq!(world.target(child, likes, 0), None);
+}
+
+#[test]
+fn singletons_are_query_sources() {
+    let (_lock, mut world) = world();
+    assert!(world.get_singleton::<Gravity>().is_none());
+    world.set_singleton(Gravity { y: -1.0 });
+    assert!(world.has_singleton::<Gravity>());
+    // Stored on the component's own entity
+    let gravity = world.component::<Gravity>();
+    assert_eq!(world.get::<Gravity>(gravity), Some(&Gravity { y: -1.0 }));
+
+    let entity = world.entity();
+    world.set(entity, Velocity::default());
+    let query = world.query::<(&mut Velocity, Singleton<&Gravity>)>();
+    query.each(&mut world, |_, (velocity, gravity)| velocity.y += gravity.y);
+    world.get_singleton_mut::<Gravity>().unwrap().y = -2.0;
+    world.singleton_modified::<Gravity>();
+    query.each(&mut world, |_, (velocity, gravity)| velocity.y += gravity.y);
+    assert_eq!(world.get::<Velocity>(entity).unwrap().y, -3.0);
+
+    // Without the singleton the query matches nothing
+    world.remove_singleton::<Gravity>();
+    assert_eq!(query.iter(&mut world).count(), 0);
+}
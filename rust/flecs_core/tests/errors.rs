@@ -0,0 +1,211 @@
+// Errors are returned and recorded for flecs_last_error instead of aborting
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{c_char, c_void, CStr};
+
+fn last_error() -> (i32, String) {
+    unsafe {
+        let message = flecs_last_error_message();
+        let message = if message.is_null() { String::new() } else { CStr::from_ptr(message).to_str().unwrap().to_string() };
+        (flecs_last_error(), message)
+    }
+}
+
+#[test]
+fn invalid_parameters_are_recoverable() {
+    let _lock = lock();
+    unsafe {
+        let tag = flecs_tag_create(c"ErrorsTag".as_ptr());
+        let entity = flecs_entity_create();
+        ecs_delete(*WORLD, entity);
+        flecs_clear_error();
+        assert_eq!(last_error(), (0, String::new()));
+
+        flecs_entity_add_tag(entity, tag);
+        let (code, message) = last_error();
+        assert_eq!(code, ECS_INVALID_PARAMETER as i32);
+        assert!(message.starts_with("assert: "), "{}", message);
+        // Reading the error doesn't clear it
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+
+        // Other threads have errors of their own
+        assert_eq!(std::thread::spawn(|| flecs_last_error()).join().unwrap(), 0);
+
+        // Failed exports without a flecs error record one of their own
+        assert!(flecs_ref_create(entity, tag).is_null());
+        assert_eq!(last_error(), (ECS_INVALID_PARAMETER as i32, "entity is not alive".to_string()));
+
+        flecs_clear_error();
+        assert_eq!(last_error(), (0, String::new()));
+    }
+}
+
+#[test]
+fn failed_queries_have_a_reason() {
+    let _lock = lock();
+    unsafe {
+        let desc = flecs_query_create();
+        (*desc).filter.expr = c"ErrorsMissing".as_ptr();
+        flecs_clear_error();
+        assert!(flecs_query_build(desc).is_null());
+
+        // The code flecs sets for an error on another thread isn't this one's
+        let tag = flecs_tag_create(c"ErrorsOtherTag".as_ptr());
+        std::thread::spawn(move || {
+            let entity = flecs_entity_create();
+            ecs_delete(*WORLD, entity);
+            flecs_entity_add_tag(entity, tag);
+        })
+        .join()
+        .unwrap();
+
+        let (code, message) = last_error();
+        assert_eq!(code, ECS_OPERATION_FAILED as i32);
+        assert!(message.contains("ErrorsMissing"), "{}", message);
+        drop(Box::from_raw(desc));
+    }
+}
+
+#[test]
+fn getters_reject_invalid_ids() {
+    let _lock = lock();
+    unsafe {
+        let marker = tag("ErrorsGetterTag");
+        let position = component("ErrorsGetterPosition", &[("x", Type::U32 as u8)]);
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, position);
+        let dead = flecs_entity_create();
+        flecs_delete_entity(dead);
+        let mut count = -1;
+
+        // Ids without data and entities that aren't alive fail instead of
+        // asserting
+        for (ptr, name) in [
+            (flecs_entity_get_component(entity, marker), "tag"),
+            (flecs_entity_get_component(entity, dead), "dead component"),
+            (flecs_entity_get_component(dead, position), "dead entity"),
+            (flecs_entity_get_pair(entity, marker, entity), "tag pair"),
+            (flecs_entity_get_pair_at(dead, position, 0), "dead entity pair"),
+            (flecs_entity_to_json(dead) as *mut c_void, "json"),
+            (flecs_entity_get_targets(dead, marker, &mut count) as *mut c_void, "targets"),
+        ] {
+            assert!(ptr.is_null(), "{}", name);
+        }
+        assert!(!flecs_entity_set_pair(dead, position, entity, std::ptr::null()));
+        flecs_clear_error();
+        flecs_entity_child_of(entity, dead);
+        assert_eq!(last_error(), (ECS_INVALID_PARAMETER as i32, "parent is not alive".to_string()));
+        assert_eq!(flecs_entity_get_parent(entity), 0);
+        flecs_clear_error();
+        flecs_entity_set_name(dead, c"ErrorsDead".as_ptr() as *mut _);
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        flecs_clear_error();
+        assert_eq!(flecs_entity_get_target(dead, marker, 0), 0);
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+
+        // The entity still works
+        assert!(!flecs_entity_get_component(entity, position).is_null());
+        flecs_delete_entity(entity);
+    }
+}
+
+#[test]
+fn query_getters_reject_invalid_ids() {
+    let _lock = lock();
+    unsafe {
+        let marker = tag("ErrorsQueryTag");
+        let position = component("ErrorsQueryPosition", &[("x", Type::U32 as u8)]);
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, position);
+        let dead = flecs_entity_create();
+        flecs_delete_entity(dead);
+
+        flecs_clear_error();
+        assert!(flecs_query_build(std::ptr::null_mut()).is_null());
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        assert!(flecs_query_iter(std::ptr::null_mut()).is_null());
+        assert!(flecs_filter_build(std::ptr::null_mut()).is_null());
+
+        let mut ids = [position];
+        let desc = flecs_query_create();
+        flecs_query_with(desc, 0, ids.as_mut_ptr(), 1);
+        let query = flecs_query_build(desc);
+        let iter = flecs_query_iter(query);
+        assert!(flecs_query_next(iter));
+        let count = flecs_iter_count(iter) as u32;
+        let mut values = [0u8; 8];
+        for (ptr, name) in [
+            (flecs_query_iter_component(values.as_mut_ptr(), 0, 1, marker) as *const c_void, "tag"),
+            (flecs_query_iter_component(values.as_mut_ptr(), 0, 1, dead) as *const c_void, "dead component"),
+            (flecs_query_iter_component(values.as_mut_ptr(), 1, 1, position) as *const c_void, "index"),
+            (flecs_filter_iter_component(values.as_mut_ptr(), 0, 1, marker) as *const c_void, "filter tag"),
+            (flecs_query_field(iter, 0, count, 0), "term 0"),
+            (flecs_query_field(iter, 2, count, 0), "term past the last"),
+            (flecs_query_field(iter, 1, count, count), "index"),
+            (flecs_query_field_list(iter, 2, count) as *const c_void, "list"),
+        ] {
+            flecs_clear_error();
+            assert!(ptr.is_null(), "{}", name);
+        }
+        flecs_clear_error();
+        assert_eq!(flecs_query_field_size(iter, 2), 0);
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        flecs_clear_error();
+        assert_eq!(flecs_query_entity(iter, count, count), 0);
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        assert_eq!(flecs_query_entity(iter, count + 1, 0), 0);
+
+        // Valid arguments still work
+        assert!(!flecs_query_field(iter, 1, count, 0).is_null());
+        assert_ne!(flecs_query_entity(iter, count, 0), 0);
+        while flecs_query_next(iter) {}
+        flecs_iter_destroy(iter);
+        flecs_query_destroy(query);
+        flecs_delete_entity(entity);
+    }
+}
+
+// flecs aborts when a component is registered again with another size. The
+// child process installs the hook and hits that error.
+unsafe fn abort_child(replace: bool) {
+    unsafe extern "C" fn replace_hook(code: i32, message: *const c_char) -> bool {
+        eprintln!("abort hook: {} {}", code, CStr::from_ptr(message).to_str().unwrap());
+        true
+    }
+    unsafe extern "C" fn keep_hook(_code: i32, _message: *const c_char) -> bool {
+        false
+    }
+    flecs_set_abort_hook(Some(if replace { replace_hook } else { keep_hook }));
+    flecs_component_create_sized(c"ErrorsResized".as_ptr(), 4, 4);
+    flecs_component_create_sized(c"ErrorsResized".as_ptr(), 8, 4);
+    unreachable!("flecs didn't abort");
+}
+
+#[test]
+fn abort_hooks_can_replace_the_abort() {
+    if let Ok(mode) = std::env::var("FLECS_CORE_ABORT_CHILD") {
+        let _lock = lock();
+        unsafe { abort_child(mode == "replace") };
+    }
+    let run = |mode: &str| {
+        std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["abort_hooks_can_replace_the_abort", "--exact", "--nocapture"])
+            .env("FLECS_CORE_ABORT_CHILD", mode)
+            .output()
+            .unwrap()
+    };
+
+    // A hook that replaces the abort exits with the error code
+    let replaced = run("replace");
+    let stderr = String::from_utf8_lossy(&replaced.stderr);
+    assert_eq!(replaced.status.code(), Some(ECS_INVALID_COMPONENT_SIZE as i32), "{}", stderr);
+    assert!(stderr.contains(&format!("abort hook: {} ", ECS_INVALID_COMPONENT_SIZE)), "{}", stderr);
+
+    // Otherwise the process aborts
+    let aborted = run("keep");
+    assert!(!aborted.status.success());
+    assert_ne!(aborted.status.code(), Some(ECS_INVALID_COMPONENT_SIZE as i32));
+}
@@ -0,0 +1,78 @@
+// Query fields matched on the iterated entities, inherited from a prefab, or
+// not matched at all by an optional term
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::c_void;
+
+#[test]
+fn fields_resolve_owned_shared_and_missing_data() {
+    let _lock = lock();
+    unsafe {
+        let position = component("FieldsPosition", &[("x", Type::F32 as u8)]);
+        let speed = component("FieldsSpeed", &[("value", Type::F32 as u8)]);
+        let health = component("FieldsHealth", &[("value", Type::F32 as u8)]);
+        let prefab = flecs_prefab_create();
+        flecs_entity_add_component(prefab, speed);
+        let shared = flecs_entity_get_component(prefab, speed);
+        flecs_component_set_member_f32(shared, 0, 7.0);
+
+        let mut instances = Vec::new();
+        for index in 0..3 {
+            let instance = flecs_prefab_instance(prefab);
+            flecs_entity_add_component(instance, position);
+            flecs_component_set_member_f32(flecs_entity_get_component(instance, position), 0, index as f32);
+            if index == 2 {
+                flecs_entity_add_component(instance, health);
+            }
+            instances.push(instance);
+        }
+
+        let mut ids = [position, speed, health];
+        let desc = flecs_query_create();
+        flecs_query_with(desc, 0, ids.as_mut_ptr(), 3);
+        (*desc).filter.terms[2].oper = ecs_oper_kind_t_EcsOptional;
+        let query = flecs_query_build(desc);
+        let iter = flecs_query_iter(query);
+
+        let mut seen = Vec::new();
+        while flecs_query_next(iter) {
+            let count = flecs_iter_count(iter) as u32;
+            assert!(flecs_field_is_self(iter, 1));
+            assert_eq!(flecs_field_src(iter, 1), 0);
+            assert_eq!(flecs_field_id(iter, 1), position);
+
+            // The inherited field is one value shared by the whole table
+            assert!(!flecs_field_is_self(iter, 2));
+            assert!(flecs_field_is_set(iter, 2));
+            assert_eq!(flecs_field_src(iter, 2), prefab);
+            assert_eq!(flecs_field_id(iter, 2), speed);
+
+            let with_health = flecs_field_is_set(iter, 3);
+            let speeds = flecs_query_field_list(iter, 2, count);
+            let healths = flecs_query_field_list(iter, 3, count);
+            for index in 0..count {
+                let entity = flecs_query_entity(iter, count, index);
+                let owned = flecs_query_field(iter, 1, count, index) as *mut c_void;
+                assert_eq!(owned, flecs_entity_get_component(entity, position));
+                assert_eq!(flecs_query_field(iter, 2, count, index), shared as *const c_void);
+                assert_eq!(*speeds.add(index as usize), shared as *const c_void);
+                assert_eq!(flecs_component_get_member_f32(shared, 0), 7.0);
+
+                // An optional term that didn't match has no data
+                let missing = flecs_query_field(iter, 3, count, index);
+                assert_eq!(missing.is_null(), !with_health);
+                assert_eq!(*healths.add(index as usize), missing);
+                seen.push((entity, with_health));
+            }
+            m_free(speeds as *mut c_void);
+            m_free(healths as *mut c_void);
+        }
+        seen.sort();
+        let mut expected = vec![(instances[0], false), (instances[1], false), (instances[2], true)];
+        expected.sort();
+        assert_eq!(seen, expected);
+    }
+}
@@ -0,0 +1,154 @@
+// Keeps flecs_core.h in sync with the extern "C" exports of the crate
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// Splits a parameter list on the commas that aren't nested in a type
+fn split_params(params: &str) -> Vec<String> {
+    let mut split = Vec::new();
+    let (mut depth, mut current) = (0, String::new());
+    for c in params.chars() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    split.push(current);
+    split.into_iter().map(|param| param.trim().to_string()).filter(|param| !param.is_empty()).collect()
+}
+
+// C spelling of a Rust type in an export signature, the way flecs_core.h
+// declares it. Callbacks are passed as Option of a flecs_*_t typedef.
+fn c_type(rust: &str) -> String {
+    let rust = rust.trim();
+    if let Some(pointee) = rust.strip_prefix("*mut ") {
+        let pointee = c_type(pointee);
+        return if pointee.ends_with('*') { format!("{}*", pointee) } else { format!("{} *", pointee) };
+    }
+    if let Some(pointee) = rust.strip_prefix("*const ") {
+        let pointee = c_type(pointee);
+        return if pointee.ends_with('*') { format!("{}const *", pointee) } else { format!("const {} *", pointee) };
+    }
+    if let Some(inner) = rust.strip_prefix("Option<").and_then(|inner| inner.strip_suffix('>')) {
+        return c_type(inner);
+    }
+    match rust {
+        "()" => "void",
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        "i8" => "int8_t",
+        "i16" => "int16_t",
+        "i32" => "int32_t",
+        "i64" => "int64_t",
+        "f32" => "float",
+        "f64" => "double",
+        "usize" => "size_t",
+        "isize" => "intptr_t",
+        "c_char" => "char",
+        "c_void" => "void",
+        "c_int" => "int",
+        other => other,
+    }
+    .to_string()
+}
+
+// Signature without parameter names or whitespace, e.g.
+// "bool(uint64_t,constchar*)"
+fn normalize(ret: &str, params: &[String]) -> String {
+    let signature = format!("{}({})", ret, params.join(","));
+    signature.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+// Signatures of the #[no_mangle] exports that are always compiled in.
+// Exports behind a cargo feature are internal hooks and stay out of the
+// header.
+fn exported_functions(source: &str, exports: &mut BTreeMap<String, String>) {
+    let lines: Vec<&str> = source.lines().collect();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim() != "#[no_mangle]" {
+            continue;
+        }
+        let feature_gated = lines[index.saturating_sub(2)..index]
+            .iter()
+            .any(|line| line.contains("#[cfg(feature"));
+        let start = index + lines[index..].iter().position(|line| line.contains(" fn ")).unwrap();
+        let end = start + lines[start..].iter().position(|line| line.contains('{')).unwrap();
+        let mut signature = lines[start..end].join(" ");
+        signature.push_str(lines[end].split('{').next().unwrap());
+
+        let signature = signature.split(" fn ").nth(1).unwrap();
+        let (name, rest) = signature.split_once('(').unwrap();
+        let name = name.trim();
+        if feature_gated || !(name.starts_with("flecs_") || name == "m_free") {
+            continue;
+        }
+        let close = rest.rfind(')').unwrap();
+        let params: Vec<String> =
+            split_params(&rest[..close]).iter().map(|param| c_type(param.split_once(':').unwrap().1)).collect();
+        let ret = rest[close + 1..].trim().strip_prefix("->").map_or("void".to_string(), c_type);
+        exports.insert(name.to_string(), normalize(&ret, &params));
+    }
+}
+
+// Signatures declared by flecs_core.h
+fn declared_functions(header: &str, declared: &mut BTreeMap<String, String>) {
+    for line in header.lines() {
+        if line.starts_with("//") || line.starts_with('#') || line.starts_with("typedef") || !line.ends_with(");") {
+            continue;
+        }
+        let (ret_and_name, rest) = line.split_once('(').unwrap();
+        let name = ret_and_name.rsplit([' ', '*']).next().unwrap();
+        let ret = &ret_and_name[..ret_and_name.len() - name.len()];
+        let params: Vec<String> = split_params(&rest[..rest.len() - 2])
+            .iter()
+            .filter(|param| param.as_str() != "void")
+            .map(|param| param.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_').to_string())
+            .collect();
+        declared.insert(name.to_string(), normalize(ret, &params));
+    }
+}
+
+fn source_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            source_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn header_declares_every_export() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut files = Vec::new();
+    source_files(&root.join("src"), &mut files);
+
+    let mut exports = BTreeMap::new();
+    for file in files {
+        exported_functions(&std::fs::read_to_string(file).unwrap(), &mut exports);
+    }
+    let mut declared = BTreeMap::new();
+    declared_functions(&std::fs::read_to_string(root.join("flecs_core.h")).unwrap(), &mut declared);
+
+    let missing: Vec<_> = exports.keys().filter(|name| !declared.contains_key(*name)).collect();
+    let stale: Vec<_> = declared.keys().filter(|name| !exports.contains_key(*name)).collect();
+    assert!(missing.is_empty(), "exports missing from flecs_core.h: {:?}", missing);
+    assert!(stale.is_empty(), "flecs_core.h declares functions that aren't exported: {:?}", stale);
+
+    let mismatched: Vec<_> = exports
+        .iter()
+        .filter(|(name, signature)| declared[*name] != **signature)
+        .map(|(name, signature)| format!("{}: exported as {}, declared as {}", name, signature, declared[name]))
+        .collect();
+    assert!(mismatched.is_empty(), "flecs_core.h signatures differ from the exports: {:#?}", mismatched);
+}
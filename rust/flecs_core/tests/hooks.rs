@@ -0,0 +1,191 @@
+// Lifecycle hooks: the defaults that own string, array and vector members,
+// and hooks routed to guest callbacks
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{c_char, c_void, CStr, CString};
+
+// Arrays start with their length
+fn u32array(values: &[u32]) -> Vec<u32> {
+    let mut array = vec![values.len() as u32];
+    array.extend_from_slice(values);
+    array
+}
+
+fn to_json(entity: ecs_entity_t) -> String {
+    unsafe {
+        let json = flecs_entity_to_json(entity);
+        let string = CStr::from_ptr(json).to_str().unwrap().to_string();
+        m_free(json as *mut _);
+        string
+    }
+}
+
+#[test]
+fn string_and_array_members_are_owned() {
+    let _lock = lock();
+    unsafe {
+        let label = component("HooksLabel", &[("id", Type::U32 as u8), ("name", Type::String as u8), ("values", Type::U32Array as u8)]);
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, label);
+        let ptr = flecs_entity_get_component(entity, label);
+        // A u32, then two pointers aligned to pointer size
+        let name_offset = std::mem::size_of::<usize>() as u32;
+        let values_offset = 2 * name_offset;
+        assert!(flecs_component_get_member_string(ptr, name_offset).is_null());
+
+        // Setters copy, and setting again frees the previous value
+        let first = CString::new("first").unwrap();
+        assert!(flecs_component_set_member_string(label, ptr, name_offset, first.as_ptr()));
+        assert_ne!(flecs_component_get_member_string(ptr, name_offset) as *const c_char, first.as_ptr());
+        let second = CString::new("label").unwrap();
+        assert!(flecs_component_set_member_string(label, ptr, name_offset, second.as_ptr()));
+        assert!(flecs_component_set_member_u32array(label, ptr, values_offset, u32array(&[1, 2, 3]).as_ptr()));
+
+        // Members of another kind are rejected, and keep their value
+        assert!(!flecs_component_set_member_string(label, ptr, 0, first.as_ptr()));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        assert!(!flecs_component_set_member_string(label, ptr, values_offset, first.as_ptr()));
+        assert!(!flecs_component_set_member_u32array(label, ptr, name_offset, u32array(&[4]).as_ptr()));
+        assert!(!flecs_component_set_member_f32array(label, ptr, values_offset, [1.0f32, 4.0].as_ptr()));
+        assert_eq!(flecs_component_get_member_u32array(ptr, values_offset).read(), 3);
+        // Meta serializes arrays as arrays, not pointers
+        let json = to_json(entity);
+        assert!(json.contains("\"name\":\"label\""), "{}", json);
+        assert!(json.contains("\"values\":[1, 2, 3]"), "{}", json);
+
+        // A copy gets values of its own
+        let clone = ecs_clone(*WORLD, 0, entity, true);
+        let copy = flecs_entity_get_component(clone, label);
+        let name = flecs_component_get_member_string(copy, name_offset);
+        assert_ne!(name, flecs_component_get_member_string(ptr, name_offset));
+        assert_eq!(CStr::from_ptr(name).to_str().unwrap(), "label");
+        let values = flecs_component_get_member_u32array(copy, values_offset);
+        assert_ne!(values, flecs_component_get_member_u32array(ptr, values_offset));
+        assert_eq!(std::slice::from_raw_parts(values, 4), &[3, 1, 2, 3]);
+
+        // Moving to another table keeps the values
+        let marker = CString::new("HooksMarker").unwrap();
+        flecs_entity_add_tag(clone, flecs_tag_create(marker.as_ptr()));
+        let moved = flecs_entity_get_component(clone, label);
+        assert_eq!(flecs_component_get_member_string(moved, name_offset), name);
+        assert_eq!(flecs_component_get_member_u32array(moved, values_offset), values);
+
+        flecs_delete_entity(clone);
+        flecs_delete_entity(entity);
+    }
+}
+
+#[test]
+fn vector_members_are_owned() {
+    let _lock = lock();
+    unsafe {
+        let path = component("HooksPath", &[("points", Type::F32Vector as u8), ("tags", Type::StringVector as u8)]);
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, path);
+        let ptr = flecs_entity_get_component(entity, path);
+        let points_offset = 0;
+        let tags_offset = std::mem::size_of::<ecs_vec_t>() as u32;
+        let mut count = -1;
+        assert!(flecs_component_get_member_vector(ptr, points_offset, &mut count).is_null());
+        assert_eq!(count, 0);
+
+        let points = [1.5f32, 2.0, 3.0];
+        assert!(flecs_component_set_member_vector(path, ptr, points_offset, points.as_ptr() as *const c_void, 3, 4));
+        let tags = [CString::new("red").unwrap(), CString::new("blue").unwrap()];
+        let tag_ptrs: Vec<*const c_char> = tags.iter().map(|tag| tag.as_ptr()).collect();
+        assert!(flecs_component_set_member_string_vector(path, ptr, tags_offset, tag_ptrs.as_ptr(), 2));
+        // Setting again replaces the elements
+        assert!(flecs_component_set_member_string_vector(path, ptr, tags_offset, tag_ptrs.as_ptr(), 1));
+        assert!(flecs_component_set_member_string_vector(path, ptr, tags_offset, tag_ptrs.as_ptr(), 2));
+
+        // Elements of another size, or members that aren't vectors of that
+        // kind, are rejected
+        let wide = [1.5f64];
+        assert!(!flecs_component_set_member_vector(path, ptr, points_offset, wide.as_ptr() as *const c_void, 1, 8));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        assert!(!flecs_component_set_member_vector(path, ptr, tags_offset, points.as_ptr() as *const c_void, 1, 8));
+        assert!(!flecs_component_set_member_vector(path, ptr, 4, points.as_ptr() as *const c_void, 3, 4));
+        assert!(!flecs_component_set_member_string_vector(path, ptr, points_offset, tag_ptrs.as_ptr(), 2));
+        // So are negative counts and byte counts that overflow
+        assert!(!flecs_component_set_member_vector(path, ptr, points_offset, points.as_ptr() as *const c_void, -1, 4));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        assert!(!flecs_component_set_member_vector(path, ptr, points_offset, points.as_ptr() as *const c_void, i32::MAX, 4));
+
+        let values = flecs_component_get_member_vector(ptr, points_offset, &mut count) as *const f32;
+        assert_eq!(std::slice::from_raw_parts(values, count as usize), &points);
+
+        // Meta serializes them as arrays
+        let json = to_json(entity);
+        assert!(json.contains("\"points\":[1.5, 2, 3]"), "{}", json);
+        assert!(json.contains("\"tags\":[\"red\", \"blue\"]"), "{}", json);
+
+        // A copy gets elements of its own
+        let clone = ecs_clone(*WORLD, 0, entity, true);
+        let copy = flecs_entity_get_component(clone, path);
+        let copied = flecs_component_get_member_vector(copy, tags_offset, &mut count) as *const *const c_char;
+        assert_eq!(count, 2);
+        let original = flecs_component_get_member_vector(ptr, tags_offset, &mut count) as *const *const c_char;
+        assert_ne!(*copied, *original);
+        assert_eq!(CStr::from_ptr(*copied.add(1)).to_str().unwrap(), "blue");
+        assert!(to_json(clone).contains("\"tags\":[\"red\", \"blue\"]"));
+
+        flecs_delete_entity(clone);
+        flecs_delete_entity(entity);
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    ctor: i32,
+    dtor: i32,
+    on_set: i32,
+}
+
+unsafe extern "C" fn ctor(ptr: *mut c_void, count: i32, size: i32, ctx: *mut c_void) {
+    assert_eq!(size, 4);
+    for index in 0..count as usize {
+        *(ptr as *mut u32).add(index) = 42;
+    }
+    (*(ctx as *mut Counts)).ctor += count;
+}
+
+unsafe extern "C" fn dtor(_ptr: *mut c_void, count: i32, _size: i32, ctx: *mut c_void) {
+    (*(ctx as *mut Counts)).dtor += count;
+}
+
+unsafe extern "C" fn on_set(iter: *mut ecs_iter_t, ctx: *mut c_void) {
+    (*(ctx as *mut Counts)).on_set += flecs_iter_count(iter);
+}
+
+#[test]
+fn hooks_call_guest_callbacks() {
+    let _lock = lock();
+    unsafe {
+        let health = component("HooksHealth", &[("value", Type::U32 as u8)]);
+        let mut counts = Counts::default();
+        let mut hooks: flecs_type_hooks_t = std::mem::zeroed();
+        hooks.ctor = Some(ctor);
+        hooks.dtor = Some(dtor);
+        hooks.on_set = Some(on_set);
+        hooks.ctx = &mut counts as *mut Counts as *mut c_void;
+        assert!(flecs_component_set_hooks(health, &hooks));
+
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, health);
+        assert_eq!(counts.ctor, 1);
+        assert_eq!(*(flecs_entity_get_component(entity, health) as *const u32), 42);
+
+        flecs_entity_component_modified(entity, health);
+        assert_eq!(counts.on_set, 1);
+
+        flecs_delete_entity(entity);
+        assert_eq!(counts.dtor, 1);
+
+        // Hooks can't change once the component is in use
+        flecs_entity_add_component(flecs_entity_create(), health);
+        assert!(!flecs_component_set_hooks(health, &hooks));
+    }
+}
@@ -0,0 +1,117 @@
+// Custom HTTP routes handled by guest callbacks
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{c_void, CStr, CString};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+fn request(method: &str, path: &str) -> (i32, String) {
+    let method = CString::new(method).unwrap();
+    let path = CString::new(path).unwrap();
+    let mut code = 0;
+    unsafe {
+        let body = flecs_http_request(method.as_ptr(), path.as_ptr(), &mut code);
+        if body.is_null() {
+            return (code, String::new());
+        }
+        let body_str = CStr::from_ptr(body).to_str().unwrap().to_string();
+        m_free(body as *mut _);
+        (code, body_str)
+    }
+}
+
+// Spawns an entity named after the prefab parameter, counting spawns in ctx
+unsafe extern "C" fn spawn(request: *const ecs_http_request_t, reply: *mut ecs_http_reply_t, ctx: *mut c_void) -> bool {
+    let prefab = flecs_http_request_param(request, c"prefab".as_ptr());
+    if prefab.is_null() {
+        flecs_http_reply_set(reply, 400, c"{\"error\":\"missing prefab\"}".as_ptr());
+        return true;
+    }
+    let entity = flecs_entity_create_named(prefab);
+    *(ctx as *mut u32) += 1;
+    let body = CString::new(format!("{{\"entity\":{}}}", entity)).unwrap();
+    flecs_http_reply_set(reply, 200, body.as_ptr());
+    true
+}
+
+unsafe extern "C" fn dispatch(
+    callback_index: u32,
+    request: *const ecs_http_request_t,
+    reply: *mut ecs_http_reply_t,
+    _ctx: *mut c_void,
+) -> bool {
+    let path = CStr::from_ptr(flecs_http_request_path(request)).to_str().unwrap();
+    let body = CString::new(format!("{{\"index\":{},\"path\":\"{}\"}}", callback_index, path)).unwrap();
+    flecs_http_reply_set(reply, 200, body.as_ptr());
+    true
+}
+
+#[test]
+fn routes_are_dispatched_to_callbacks() {
+    let _lock = lock();
+    let mut spawned = 0u32;
+    unsafe {
+        flecs_http_route(HttpMethod::Get as u8, c"/game/spawn".as_ptr(), spawn, &mut spawned as *mut u32 as *mut c_void);
+        flecs_set_http_dispatcher(Some(dispatch));
+        flecs_http_route_indexed(HttpMethod::Post as u8, c"/game/save".as_ptr(), 7, std::ptr::null_mut());
+    }
+
+    let (code, body) = request("GET", "/game/spawn?prefab=HttpOrc");
+    assert_eq!(code, 200);
+    assert_eq!(spawned, 1);
+    let entity = unsafe { flecs_entity_create_named(c"HttpOrc".as_ptr()) };
+    assert_eq!(body, format!("{{\"entity\":{}}}", entity));
+
+    let (code, body) = request("GET", "/game/spawn");
+    assert_eq!(code, 400);
+    assert_eq!(body, "{\"error\":\"missing prefab\"}");
+    assert_eq!(request("POST", "/game/spawn?prefab=HttpOrc").0, 404);
+    assert_eq!(request("GET", "/game/missing").0, 404);
+
+    let (code, body) = request("POST", "/game/save");
+    assert_eq!(code, 200);
+    assert_eq!(body, "{\"index\":7,\"path\":\"game/save\"}");
+
+    unsafe {
+        flecs_http_route_remove(HttpMethod::Get as u8, c"/game/spawn".as_ptr());
+    }
+    assert_eq!(request("GET", "/game/spawn?prefab=HttpOrc").0, 404);
+    assert_eq!(spawned, 1);
+}
+
+#[test]
+fn routes_answer_local_clients() {
+    let _lock = lock();
+    let mut spawned = 0u32;
+    unsafe {
+        flecs_http_route(HttpMethod::Get as u8, c"/game/spawn".as_ptr(), spawn, &mut spawned as *mut u32 as *mut c_void);
+        assert!(flecs_http_enable(27762));
+    }
+
+    let client = std::thread::spawn(|| {
+        let mut stream = connect(27762);
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        stream.write_all(b"GET /game/spawn?prefab=HttpGoblin HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    });
+    while !client.is_finished() {
+        unsafe {
+            flecs_progress(0.0);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let response = client.join().unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.ends_with('}'), "{}", response);
+    assert_eq!(spawned, 1);
+
+    unsafe {
+        flecs_http_disable();
+        flecs_http_route_remove(HttpMethod::Get as u8, c"/game/spawn".as_ptr());
+    }
+}
@@ -0,0 +1,267 @@
+// Round-trip checks for ids crossing the flecs_* exports. Entity ids are
+// 64-bit: the upper half carries the generation of recycled ids and the
+// pair flag, and none of that may get lost on the way in or out.
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{c_void, CString};
+
+// Entity whose id has generation bits set, by recycling a deleted id
+unsafe fn recycled_entity() -> ecs_entity_t {
+    let entity = flecs_entity_create();
+    flecs_delete_entity(entity);
+    let recycled = flecs_entity_create();
+    assert_ne!(recycled >> 32, 0, "expected a recycled id with a generation");
+    recycled
+}
+
+#[test]
+fn entity_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        let entity = recycled_entity();
+        assert!(flecs_is_valid(entity));
+
+        let name = CString::new("IdAbiNamed").unwrap();
+        let named = flecs_entity_create_named(name.as_ptr());
+        assert_eq!(flecs_component_lookup(name.as_ptr() as *mut _), named);
+        assert_eq!(flecs_component_get(name.as_ptr()), named);
+
+        let renamed = CString::new("IdAbiRenamed").unwrap();
+        flecs_entity_set_name(entity, renamed.as_ptr() as *mut _);
+        assert_eq!(flecs_component_lookup(renamed.as_ptr() as *mut _), entity);
+
+        flecs_delete_entity(entity);
+        assert!(!flecs_is_valid(entity));
+    }
+}
+
+#[test]
+fn component_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        let position = component("IdAbiPosition", &[("value", Type::U64 as u8)]);
+        let marker = tag("IdAbiMarker");
+        let entity = recycled_entity();
+
+        flecs_entity_add_component(entity, position);
+        flecs_entity_add_tag(entity, marker);
+        assert!(flecs_entity_has_component(entity, position));
+        assert!(flecs_entity_has_component(entity, marker));
+
+        let value = flecs_entity_get_component(entity, position);
+        flecs_component_set_member_u64(value, 0, u64::MAX);
+        assert_eq!(flecs_component_get_member_u64(flecs_entity_get_component(entity, position), 0), u64::MAX);
+
+        flecs_entity_remove_component(entity, position);
+        assert!(!flecs_entity_has_component(entity, position));
+
+        flecs_singleton_add(position);
+        assert!(!flecs_singleton_get(position).is_null());
+        flecs_singleton_remove(position);
+    }
+}
+
+#[test]
+fn pair_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        let likes = tag("IdAbiLikes");
+        let target = recycled_entity();
+        let entity = recycled_entity();
+
+        let pair = flecs_make_pair(likes, target);
+        assert!(flecs_is_pair(pair));
+        assert_eq!(flecs_pair_first(pair), likes);
+        assert_eq!(flecs_pair_second(pair), target);
+
+        flecs_entity_add_pair(entity, likes, target);
+        assert!(flecs_entity_has_pair(entity, likes, target));
+        assert!(flecs_entity_has_component(entity, pair));
+        assert_eq!(flecs_entity_get_target(entity, likes, 0), target);
+
+        let mut count = 0;
+        let targets = flecs_entity_get_targets(entity, likes, &mut count);
+        assert_eq!(count, 1);
+        assert_eq!(*targets, target);
+        m_free(targets as *mut c_void);
+
+        flecs_entity_remove_pair(entity, likes, target);
+        assert!(!flecs_entity_has_pair(entity, likes, target));
+
+        let parent = recycled_entity();
+        let child = recycled_entity();
+        flecs_entity_child_of(child, parent);
+        assert_eq!(flecs_entity_get_parent(child), parent);
+
+        let iter = flecs_entity_children(parent);
+        assert!(flecs_term_next(iter));
+        assert_eq!(flecs_iter_count(iter), 1);
+        assert_eq!(*flecs_child_entities(iter), child);
+        let mut entities_count = 0;
+        assert_eq!(*flecs_iter_entities(iter, &mut entities_count), child);
+        assert_eq!(entities_count, 1);
+        while flecs_term_next(iter) {}
+
+        flecs_delete_with(flecs_make_pair(EcsChildOf, parent));
+        assert!(!flecs_is_valid(child));
+
+        let prefab = flecs_prefab_create();
+        let instance = flecs_prefab_instance(prefab);
+        assert_eq!(flecs_entity_get_target(instance, EcsIsA, 0), prefab);
+    }
+}
+
+#[test]
+fn bulk_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        // Recycle a batch of ids so bulk creation hands out ids with generations
+        let recycled: Vec<ecs_entity_t> = (0..4).map(|_| flecs_entity_create()).collect();
+        for entity in &recycled {
+            flecs_delete_entity(*entity);
+        }
+
+        let marker = tag("IdAbiBulkMarker");
+        let relation = tag("IdAbiBulkRelation");
+        let target = recycled_entity();
+        let ids = [marker, flecs_make_pair(relation, target)];
+        let entities = flecs_entity_create_bulk_components(4, ids.len() as u32, ids.as_ptr());
+        let entities = std::slice::from_raw_parts(entities, 4).to_vec();
+        assert!(entities.iter().any(|entity| entity >> 32 != 0));
+        for entity in &entities {
+            assert!(flecs_is_valid(*entity));
+            assert!(flecs_entity_has_component(*entity, marker));
+            assert!(flecs_entity_has_pair(*entity, relation, target));
+        }
+
+        let position = component("IdAbiBulkPosition", &[("value", Type::U64 as u8)]);
+        let values: [u64; 3] = [1, 2, 3];
+        let data = [values.as_ptr() as *mut c_void];
+        let spawned = flecs_entity_create_bulk_data(3, 1, &position, data.as_ptr());
+        let spawned_ids = std::slice::from_raw_parts(spawned, 3).to_vec();
+        for (entity, value) in spawned_ids.iter().zip(values) {
+            let ptr = flecs_entity_get_component(*entity, position);
+            assert_eq!(flecs_component_get_member_u64(ptr, 0), value);
+        }
+        m_free(spawned as *mut c_void);
+
+        // Without components the arrays may be null, with them they can't be
+        let empty = flecs_entity_create_bulk_data(2, 0, std::ptr::null(), std::ptr::null());
+        assert!(flecs_is_valid(*empty.add(1)));
+        m_free(empty as *mut c_void);
+        assert!(flecs_entity_create_bulk_data(2, 1, &position, std::ptr::null()).is_null());
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+
+        flecs_remove_all(marker);
+        assert!(!flecs_entity_has_component(entities[0], marker));
+    }
+}
+
+#[test]
+fn query_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        let relation = tag("IdAbiQueryRelation");
+        let target = recycled_entity();
+        let entity = recycled_entity();
+        flecs_entity_add_pair(entity, relation, target);
+
+        let mut ids = [flecs_make_pair(relation, flecs_wildcard())];
+        let desc = flecs_query_create();
+        flecs_query_with(desc, 0, ids.as_mut_ptr(), 1);
+        let query = flecs_query_build(desc);
+        let iter = flecs_query_iter(query);
+
+        let mut found = false;
+        while flecs_query_next(iter) {
+            let count = flecs_iter_count(iter) as u32;
+            for index in 0..count {
+                if flecs_query_entity(iter, count, index) == entity {
+                    found = true;
+                    assert_eq!(flecs_field_id(iter, 1), flecs_make_pair(relation, target));
+                    assert_eq!(flecs_iter_pair_target(iter, 1), target);
+                    assert_eq!(*flecs_query_entity_list(iter).add(index as usize), entity);
+                }
+            }
+        }
+        assert!(found);
+
+        let mut filter_ids = [flecs_make_pair(relation, target)];
+        let filter_desc = flecs_filter_create();
+        flecs_filter_with(filter_desc, 0, filter_ids.as_mut_ptr(), 1);
+        let filter = flecs_filter_build(filter_desc);
+        let iter = flecs_filter_iter(filter);
+        assert!(flecs_filter_next(iter));
+        assert_eq!(flecs_filter_entity(iter, 1, 0), entity);
+        assert_eq!(*flecs_filter_entity_list(iter), entity);
+        while flecs_filter_next(iter) {}
+    }
+}
+
+#[test]
+fn query_term_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        let relation = tag("IdAbiTermRelation");
+        let marker = tag("IdAbiTermMarker");
+        let (first, second, excluded) = (recycled_entity(), recycled_entity(), recycled_entity());
+        let with_first = flecs_entity_create();
+        flecs_entity_add_pair(with_first, relation, first);
+        let with_second = flecs_entity_create();
+        flecs_entity_add_pair(with_second, relation, second);
+        let with_excluded = flecs_entity_create();
+        flecs_entity_add_pair(with_excluded, relation, first);
+        flecs_entity_add_pair(with_excluded, relation, excluded);
+        for entity in [with_first, with_second, with_excluded] {
+            flecs_entity_add_component(entity, marker);
+        }
+
+        let mut any_of = [flecs_make_pair(relation, first), flecs_make_pair(relation, second)];
+        let mut none_of = [flecs_make_pair(relation, excluded)];
+        let mut all_of = [marker];
+        let desc = flecs_query_create();
+        flecs_query_without(desc, 0, none_of.as_mut_ptr(), 1);
+        flecs_query_with_or(desc, 1, any_of.as_mut_ptr(), 2);
+        // An OR chain has to be followed by an AND term
+        flecs_query_with(desc, 3, all_of.as_mut_ptr(), 1);
+        let query = flecs_query_build(desc);
+        assert!(!query.is_null());
+        let iter = flecs_query_iter(query);
+
+        let mut found = Vec::new();
+        while flecs_query_next(iter) {
+            let count = flecs_iter_count(iter) as u32;
+            for index in 0..count {
+                found.push(flecs_query_entity(iter, count, index));
+            }
+        }
+        found.sort();
+        let mut expected = vec![with_first, with_second];
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn children_filter_ids_round_trip() {
+    let _lock = lock();
+    unsafe {
+        let parent = recycled_entity();
+        let child = flecs_entity_create();
+        flecs_entity_child_of(child, parent);
+
+        let filter = flecs_filter_children_init(parent);
+        let iter = flecs_filter_iter(filter);
+        let mut children = Vec::new();
+        while flecs_filter_next(iter) {
+            let count = flecs_iter_count(iter) as u32;
+            for index in 0..count {
+                children.push(flecs_filter_entity(iter, count, index));
+            }
+        }
+        assert_eq!(children, vec![child]);
+    }
+}
@@ -0,0 +1,89 @@
+// Routing flecs log messages to callbacks and the log crate
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{c_char, c_void, CStr};
+use std::sync::Mutex;
+
+static RECORDS: Mutex<Vec<(String, i32, String)>> = Mutex::new(Vec::new());
+
+unsafe extern "C" fn record(level: i32, file: *const c_char, line: i32, msg: *const c_char, ctx: *mut c_void) {
+    *(ctx as *mut i32) += 1;
+    let file = CStr::from_ptr(file).to_str().unwrap();
+    let msg = CStr::from_ptr(msg).to_str().unwrap();
+    RECORDS.lock().unwrap().push((format!("{}:{}", file, line), level, msg.to_string()));
+}
+
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let location = format!("{}:{}", record.file().unwrap(), record.line().unwrap());
+        let level = match record.level() {
+            log::Level::Warn => -2,
+            log::Level::Info => 0,
+            _ => 1,
+        };
+        RECORDS.lock().unwrap().push((location, level, format!("{}: {}", record.target(), record.args())));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Logger = Logger;
+
+fn take_records() -> Vec<(String, i32, String)> {
+    std::mem::take(&mut *RECORDS.lock().unwrap())
+}
+
+#[test]
+fn log_messages_reach_the_callback() {
+    let _lock = lock();
+    let mut calls = 0;
+    unsafe {
+        flecs_log_set_callback(Some(record), &mut calls as *mut i32 as *mut c_void);
+        let level = flecs_log_set_level(0);
+        assert_eq!(flecs_log_get_level(), 0);
+
+        ecs_log_(0, c"game.c".as_ptr(), 12, c"spawned %s".as_ptr(), c"orc".as_ptr());
+        ecs_log_(1, c"game.c".as_ptr(), 13, c"hidden".as_ptr());
+        ecs_log_(-2, c"game.c".as_ptr(), 14, c"low health".as_ptr());
+        assert_eq!(calls, 2);
+        assert_eq!(
+            take_records(),
+            vec![
+                ("game.c:12".to_string(), 0, "spawned orc".to_string()),
+                ("game.c:14".to_string(), -2, "low health".to_string()),
+            ]
+        );
+
+        flecs_log_set_level(level);
+        flecs_log_set_callback(None, std::ptr::null_mut());
+        ecs_log_(-2, c"game.c".as_ptr(), 15, c"to the console".as_ptr());
+        assert_eq!(calls, 2);
+    }
+}
+
+#[test]
+fn log_messages_reach_the_log_crate() {
+    let _lock = lock();
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+    unsafe {
+        flecs_log_to_rust();
+        let level = flecs_log_set_level(1);
+        ecs_log_(-2, c"game.c".as_ptr(), 20, c"low health".as_ptr());
+        // Below the max level of the log crate
+        ecs_log_(1, c"game.c".as_ptr(), 21, c"debug".as_ptr());
+        assert_eq!(take_records(), vec![("game.c:20".to_string(), -2, "flecs: low health".to_string())]);
+
+        flecs_log_set_level(level);
+        flecs_log_set_callback(None, std::ptr::null_mut());
+    }
+}
@@ -0,0 +1,56 @@
+// Metrics on component members and ids
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+
+#[test]
+fn member_and_id_metrics_aggregate() {
+    let _lock = lock();
+    unsafe {
+        let health = component("MetricsHealth", &[("value", Type::F32 as u8)]);
+        let enemy = flecs_tag_create(c"MetricsEnemy".as_ptr());
+        let mut entities = Vec::new();
+        for value in [10.0, 20.0, 60.0] {
+            let entity = flecs_entity_create();
+            flecs_entity_add_component(entity, health);
+            flecs_component_set_member_f32(flecs_entity_get_component(entity, health), 0, value);
+            flecs_entity_add_tag(entity, enemy);
+            entities.push(entity);
+        }
+
+        let gauge = flecs_metric_create_member(c"metrics.health".as_ptr(), health, c"value".as_ptr(), MetricKind::Gauge as u8);
+        assert_ne!(gauge, 0);
+        let enemies = flecs_metric_create_id(c"metrics.enemies".as_ptr(), enemy, MetricKind::Gauge as u8, false);
+        assert_ne!(enemies, 0);
+        assert_eq!(flecs_metric_create_member(std::ptr::null(), health, c"missing".as_ptr(), MetricKind::Gauge as u8), 0);
+        assert_eq!(flecs_metric_create_id(std::ptr::null(), enemy, 42, false), 0);
+
+        let mut value = flecs_metric_value_t::default();
+        assert!(!flecs_metric_value(enemy, &mut value));
+        let mut count = -1;
+        assert!(flecs_metric_instances(enemy, &mut count).is_null());
+        assert_eq!(count, 0);
+        flecs_progress(0.0);
+
+        assert!(flecs_metric_value(gauge, &mut value));
+        assert_eq!(value.count, 3);
+        assert_eq!(value.sum, 90.0);
+        assert_eq!(value.min, 10.0);
+        assert_eq!(value.max, 60.0);
+        assert_eq!(value.avg, 30.0);
+
+        let instances = flecs_metric_instances(gauge, &mut count);
+        let mut sources: Vec<ecs_entity_t> =
+            std::slice::from_raw_parts(instances, count as usize).iter().map(|instance| instance.source).collect();
+        sources.sort();
+        assert_eq!(sources, entities);
+        m_free(instances as *mut _);
+
+        // A gauge on an id is 1 for every entity that has it
+        assert!(flecs_metric_value(enemies, &mut value));
+        assert_eq!(value.count, 3);
+        assert_eq!(value.sum, 3.0);
+    }
+}
@@ -0,0 +1,43 @@
+// Refs to a component of an entity
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::CString;
+
+#[test]
+fn refs_follow_table_moves() {
+    let _lock = lock();
+    unsafe {
+        let name = CString::new("RefsHealth").unwrap();
+        let health = flecs_component_create_sized(name.as_ptr(), 4, 4);
+        let entity = flecs_entity_create();
+        *(flecs_entity_get_component(entity, health) as *mut u32) = 10;
+
+        let health_ref = flecs_ref_create(entity, health);
+        assert_eq!(*(flecs_ref_get(health_ref) as *const u32), 10);
+
+        // Adding a tag moves the entity to another table
+        let tag = CString::new("RefsMarker").unwrap();
+        flecs_entity_add_tag(entity, flecs_tag_create(tag.as_ptr()));
+        flecs_ref_update(health_ref);
+        let ptr = flecs_ref_get(health_ref);
+        assert_eq!(ptr, flecs_entity_get_component(entity, health));
+        assert_eq!(*(ptr as *const u32), 10);
+
+        flecs_entity_remove_component(entity, health);
+        assert!(flecs_ref_get(health_ref).is_null());
+
+        // A deleted entity's recycled id doesn't resolve through the ref
+        flecs_entity_add_component(entity, health);
+        flecs_delete_entity(entity);
+        let recycled = flecs_entity_create();
+        flecs_entity_add_component(recycled, health);
+        assert!(flecs_ref_get(health_ref).is_null());
+        flecs_ref_destroy(health_ref);
+
+        assert!(flecs_ref_create(entity, health).is_null());
+        flecs_delete_entity(recycled);
+    }
+}
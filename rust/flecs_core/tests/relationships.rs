@@ -0,0 +1,124 @@
+// Relationship traits, cleanup policies and pairs carrying data
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::c_void;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn relationship_traits_are_added() {
+    let _lock = lock();
+    unsafe {
+        let likes = tag("RelationshipsLikes");
+        assert!(flecs_relationship_add_trait(likes, RelationshipTrait::Exclusive as u8));
+        let (alice, bob, carol) = (flecs_entity_create(), flecs_entity_create(), flecs_entity_create());
+        flecs_entity_add_pair(alice, likes, bob);
+        flecs_entity_add_pair(alice, likes, carol);
+        assert!(!flecs_entity_has_pair(alice, likes, bob));
+        assert!(flecs_entity_has_pair(alice, likes, carol));
+
+        flecs_clear_error();
+        assert!(!flecs_relationship_add_trait(likes, 42));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+    }
+}
+
+#[test]
+fn cleanup_policies_are_added() {
+    let _lock = lock();
+    unsafe {
+        let owned_by = tag("RelationshipsOwnedBy");
+        assert!(flecs_entity_add_cleanup_policy(
+            owned_by,
+            CleanupCondition::OnDeleteTarget as u8,
+            CleanupAction::Delete as u8,
+        ));
+        let owner = flecs_entity_create();
+        let item = flecs_entity_create();
+        flecs_entity_add_pair(item, owned_by, owner);
+        flecs_delete_entity(owner);
+        assert!(!flecs_is_valid(item));
+
+        flecs_clear_error();
+        assert!(!flecs_entity_add_cleanup_policy(owned_by, 42, CleanupAction::Remove as u8));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+        assert!(!flecs_entity_add_cleanup_policy(owned_by, CleanupCondition::OnDelete as u8, 42));
+    }
+}
+
+#[test]
+fn relationship_carries_pair_data() {
+    let _lock = lock();
+    unsafe {
+        let owes = component("RelationshipsOwes", &[("amount", Type::U32 as u8)]);
+        let (debtor, bob, carol) = (flecs_entity_create(), flecs_entity_create(), flecs_entity_create());
+        assert_eq!(flecs_pair_typeid(owes, bob), owes);
+
+        let amount: u32 = 10;
+        assert!(flecs_entity_set_pair(debtor, owes, bob, &amount as *const u32 as *const c_void));
+        let amount: u32 = 20;
+        assert!(flecs_entity_set_pair(debtor, owes, carol, &amount as *const u32 as *const c_void));
+        assert_eq!(flecs_component_get_member_u32(flecs_entity_get_pair(debtor, owes, bob), 0), 10);
+
+        // Pair data is walked by target index
+        let mut amounts = Vec::new();
+        let mut index = 0;
+        loop {
+            let data = flecs_entity_get_pair_at(debtor, owes, index);
+            if data.is_null() {
+                break;
+            }
+            amounts.push(flecs_component_get_member_u32(data, 0));
+            index += 1;
+        }
+        amounts.sort();
+        assert_eq!(amounts, vec![10, 20]);
+
+        // Writes through the pointer run OnSet observers once marked modified
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let observed = seen.clone();
+        let observer_desc = observer_create_closure(move |iter| {
+            observed.lock().unwrap().extend_from_slice(iter.entities());
+        });
+        assert!(flecs_observer_add_event(observer_desc, Event::OnSet as u8));
+        let mut ids = [flecs_make_pair(owes, carol)];
+        flecs_filter_with(flecs_filter_from_observer_desc(observer_desc), 0, ids.as_mut_ptr(), 1);
+        let observer = flecs_observer_build(observer_desc);
+        flecs_component_set_member_u32(flecs_entity_get_pair(debtor, owes, carol), 0, 25);
+        assert!(seen.lock().unwrap().is_empty());
+        flecs_entity_pair_modified(debtor, owes, carol);
+        assert_eq!(*seen.lock().unwrap(), vec![debtor]);
+        assert_eq!(flecs_component_get_member_u32(flecs_entity_get_pair(debtor, owes, carol), 0), 25);
+        flecs_delete_entity(observer);
+    }
+}
+
+#[test]
+fn target_carries_pair_data() {
+    let _lock = lock();
+    unsafe {
+        let begin = tag("RelationshipsBegin");
+        let position = component("RelationshipsPosition", &[("x", Type::F32 as u8), ("y", Type::F32 as u8)]);
+        let entity = flecs_entity_create();
+        assert_eq!(flecs_pair_typeid(begin, position), position);
+
+        let data: [f32; 2] = [1.5, 2.5];
+        assert!(flecs_entity_set_pair(entity, begin, position, data.as_ptr() as *const c_void));
+        let pair = flecs_entity_get_pair(entity, begin, position);
+        assert_eq!(flecs_component_get_member_f32(pair, 0), 1.5);
+        assert_eq!(flecs_component_get_member_f32(pair, 4), 2.5);
+        assert_eq!(flecs_component_get_member_f32(flecs_entity_get_pair_at(entity, begin, 0), 4), 2.5);
+        assert!(flecs_entity_get_pair_at(entity, begin, 1).is_null());
+
+        // Neither element of a pair of tags carries data
+        let likes = tag("RelationshipsLikesData");
+        let other = flecs_entity_create();
+        assert_eq!(flecs_pair_typeid(likes, other), 0);
+        flecs_clear_error();
+        assert!(!flecs_entity_set_pair(entity, likes, other, data.as_ptr() as *const c_void));
+        assert!(!flecs_entity_has_pair(entity, likes, other));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+    }
+}
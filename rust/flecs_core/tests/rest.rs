@@ -0,0 +1,72 @@
+// REST server for the flecs explorer, over a socket and by dispatch
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn request(method: &str, path: &str) -> (i32, String) {
+    let method = CString::new(method).unwrap();
+    let path = CString::new(path).unwrap();
+    let mut code = 0;
+    unsafe {
+        let body = flecs_rest_request(method.as_ptr(), path.as_ptr(), &mut code);
+        if body.is_null() {
+            return (code, String::new());
+        }
+        let body_str = CStr::from_ptr(body).to_str().unwrap().to_string();
+        m_free(body as *mut _);
+        (code, body_str)
+    }
+}
+
+#[test]
+fn rest_requests_are_dispatched() {
+    let _lock = lock();
+    unsafe {
+        flecs_entity_create_named(c"rest_dispatched".as_ptr());
+    }
+    let (code, body) = request("GET", "/entity/rest_dispatched");
+    assert_eq!(code, 200);
+    assert!(body.contains("\"path\":\"rest_dispatched\""), "{}", body);
+    let (code, _) = request("GET", "/entity/rest_missing");
+    assert_eq!(code, 404);
+}
+
+#[test]
+fn rest_server_answers_local_clients() {
+    let _lock = lock();
+    unsafe {
+        flecs_entity_create_named(c"rest_served".as_ptr());
+        assert!(flecs_rest_enable(27761));
+        assert!(!flecs_rest_enable(27761));
+    }
+
+    let client = std::thread::spawn(|| {
+        let mut stream = connect(27761);
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        stream.write_all(b"GET /entity/rest_served HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    });
+    // The server answers queued requests after each frame
+    while !client.is_finished() {
+        unsafe {
+            flecs_progress(0.0);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let response = client.join().unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    assert!(response.contains("\"path\":\"rest_served\""), "{}", response);
+
+    unsafe {
+        flecs_rest_disable();
+    }
+    assert!(TcpStream::connect(("127.0.0.1", 27761)).is_err());
+}
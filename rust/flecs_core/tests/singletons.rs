@@ -0,0 +1,78 @@
+// Singletons set through the flecs_singleton_* exports, and query terms that
+// match them with the $ source
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+use std::ffi::c_void;
+
+#[test]
+fn singleton_terms_return_the_next_free_index() {
+    let _lock = lock();
+    unsafe {
+        let marker = tag("SingletonsMarker");
+        let (game, time) = (tag("SingletonsGame"), tag("SingletonsTime"));
+        flecs_singleton_add(game);
+        flecs_singleton_add(time);
+        let entity = flecs_entity_create();
+        flecs_entity_add_tag(entity, marker);
+
+        let mut ids = [marker];
+        let mut singletons = [game, time];
+        let filter_desc = flecs_filter_create();
+        flecs_filter_with(filter_desc, 0, ids.as_mut_ptr(), 1);
+        assert_eq!(flecs_filter_with_singleton(filter_desc, 1, singletons.as_mut_ptr(), 2), 3);
+        // Without ids the index stays where it was
+        assert_eq!(flecs_filter_with_singleton(filter_desc, 3, singletons.as_mut_ptr(), 0), 3);
+        let query_desc = flecs_query_create();
+        assert_eq!(flecs_query_with_singleton(query_desc, 2, singletons.as_mut_ptr(), 2), 4);
+        assert_eq!(flecs_query_with_singleton(query_desc, 4, singletons.as_mut_ptr(), 0), 4);
+
+        let filter = flecs_filter_build(filter_desc);
+        let iter = flecs_filter_iter(filter);
+        assert!(flecs_filter_next(iter));
+        assert_eq!(flecs_filter_entity(iter, 1, 0), entity);
+        while flecs_filter_next(iter) {}
+        flecs_singleton_remove(game);
+        flecs_singleton_remove(time);
+    }
+}
+
+#[test]
+fn queries_match_singletons_that_were_set() {
+    let _lock = lock();
+    unsafe {
+        let position = component("SingletonsPosition", &[("x", Type::U32 as u8)]);
+        let gravity = component("SingletonsGravity", &[("y", Type::U32 as u8)]);
+        let entity = flecs_entity_create();
+        flecs_entity_add_component(entity, position);
+
+        let mut ids = [position];
+        let mut singletons = [gravity];
+        let desc = flecs_query_create();
+        flecs_query_with(desc, 0, ids.as_mut_ptr(), 1);
+        flecs_query_with_singleton(desc, 1, singletons.as_mut_ptr(), 1);
+        let query = flecs_query_build(desc);
+
+        // Not set yet, so nothing matches
+        let iter = flecs_query_iter(query);
+        assert!(!flecs_query_next(iter));
+
+        let value: u32 = 7;
+        flecs_singleton_set(gravity, &value as *const u32 as *const c_void);
+        let iter = flecs_query_iter(query);
+        assert!(flecs_query_next(iter));
+        assert_eq!(flecs_query_entity(iter, 1, 0), entity);
+        // The field is shared, and comes from the singleton
+        assert!(!flecs_field_is_self(iter, 2));
+        let field = flecs_query_field(iter, 2, 1, 0);
+        assert_eq!(field, flecs_singleton_get(gravity));
+        assert_eq!(flecs_component_get_member_u32(field as *mut c_void, 0), 7);
+        while flecs_query_next(iter) {}
+
+        flecs_singleton_remove(gravity);
+        flecs_query_destroy(query);
+        flecs_delete_entity(entity);
+    }
+}
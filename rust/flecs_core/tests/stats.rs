@@ -0,0 +1,66 @@
+// World and pipeline statistics
+
+mod common;
+
+use common::*;
+use flecs_core::ecs::*;
+use flecs_core::*;
+use std::ffi::CStr;
+
+#[derive(Clone, Copy, Default)]
+struct Counter {
+    value: u32,
+}
+
+impl Component for Counter {
+    const NAME: &'static str = concat!(module_path!(), "::Counter");
+}
+
+#[test]
+fn stats_sample_world_and_systems() {
+    let (_lock, mut world) = world();
+    for _ in 0..3 {
+        let entity = world.entity();
+        world.set(entity, Counter::default());
+    }
+    let system = world.system::<(&mut Counter,), _>(|_, (counter,)| counter.value += 1);
+    unsafe { ecs_set_name(*WORLD, system.entity().id(), c"Stats\"Counter\"".as_ptr()) };
+
+    unsafe {
+        let stats = flecs_stats_create();
+        // Nothing was sampled yet
+        let mut count = -1;
+        assert!(flecs_stats_systems(stats, &mut count).is_null());
+        assert_eq!(count, 0);
+        flecs_stats_update(stats);
+        world.progress(0.0);
+        world.progress(0.0);
+        flecs_stats_update(stats);
+
+        let mut world_stats = flecs_world_stats_t::default();
+        flecs_stats_world(stats, &mut world_stats);
+        assert_eq!(world_stats.frame_count, 2);
+        assert!(world_stats.entity_count >= 3);
+        assert!(world_stats.system_count >= 1);
+        assert!(world_stats.systems_ran >= 2);
+
+        let systems = flecs_stats_systems(stats, &mut count);
+        let systems_slice = std::slice::from_raw_parts(systems, count as usize);
+        let system_stats = systems_slice.iter().find(|stats| stats.system == system.entity().id()).unwrap();
+        assert_eq!(system_stats.invoke_count, 2);
+        assert_eq!(system_stats.matched_entity_count, 3);
+        m_free(systems as *mut _);
+
+        let json = flecs_stats_to_json(stats);
+        let json_str = CStr::from_ptr(json).to_str().unwrap();
+        assert!(json_str.starts_with("{\"world\":{\"entity_count\":"), "{}", json_str);
+        assert!(json_str.contains("\"frame_count\":2,"), "{}", json_str);
+        assert!(json_str.contains(&format!("{{\"id\":{},", system.entity().id())), "{}", json_str);
+        // Names are escaped
+        assert!(json_str.contains(r#""name":"Stats\"Counter\"""#), "{}", json_str);
+        m_free(json as *mut _);
+
+        flecs_stats_destroy(stats);
+    }
+    system.delete(&mut world);
+}
@@ -0,0 +1,93 @@
+// Iterating the tables matched by a query and their columns
+
+mod common;
+
+use common::*;
+use flecs_core::*;
+
+unsafe fn position_x(entity: ecs_entity_t, position: ecs_entity_t) -> f32 {
+    flecs_component_get_member_f32(flecs_entity_get_component(entity, position), 0)
+}
+
+// Iterates a query to the end, so that it only reports changes made after
+unsafe fn changed(query: *mut ecs_query_t) -> bool {
+    let changed = ecs_query_changed(query, std::ptr::null());
+    let iter = flecs_query_iter(query);
+    while flecs_query_next(iter) {}
+    flecs_iter_destroy(iter);
+    changed
+}
+
+#[test]
+fn tables_are_iterated_by_column() {
+    let _lock = lock();
+    unsafe {
+        let position = component("TablesPosition", &[("x", Type::F32 as u8), ("y", Type::F32 as u8)]);
+        let velocity = component("TablesVelocity", &[("x", Type::F32 as u8), ("y", Type::F32 as u8)]);
+        let frozen = tag("TablesFrozen");
+        let mut entities = Vec::new();
+        for index in 0..5 {
+            let entity = flecs_entity_create();
+            flecs_entity_add_component(entity, position);
+            flecs_entity_add_component(entity, velocity);
+            flecs_component_set_member_f32(flecs_entity_get_component(entity, velocity), 0, index as f32);
+            if index >= 3 {
+                flecs_entity_add_tag(entity, frozen);
+            }
+            entities.push(entity);
+        }
+
+        let mut ids = [position, velocity];
+        let desc = flecs_query_create();
+        flecs_query_with(desc, 0, ids.as_mut_ptr(), 2);
+        let query = flecs_query_build(desc);
+        let mut reader_ids = [position];
+        let reader_desc = flecs_query_create();
+        flecs_query_with(reader_desc, 0, reader_ids.as_mut_ptr(), 1);
+        // A reader doesn't mark what it reads as changed
+        (*reader_desc).filter.terms[0].inout = ecs_inout_kind_t_EcsIn;
+        let reader = flecs_query_build(reader_desc);
+        assert!(changed(reader));
+        assert!(!changed(reader));
+
+        // Writes are only seen by change detection for populated tables
+        let mut rows = Vec::new();
+        let mut column_ids = [position, velocity, frozen];
+        let mut columns = [flecs_column_t { ptr: std::ptr::null_mut(), stride: 0, count: 0 }; 3];
+        let iter = flecs_query_iter(query);
+        while flecs_query_next_table(iter) {
+            let table = flecs_iter_table(iter);
+            assert_eq!(flecs_table_get_column_index(table, frozen), -1);
+            assert_ne!(flecs_table_get_column_index(table, position), -1);
+            assert_eq!(flecs_table_get_column_size(table, flecs_table_get_column_index(table, position)), 8);
+
+            let count = flecs_table_columns(table, column_ids.as_mut_ptr(), 3, columns.as_mut_ptr());
+            assert_eq!(count, flecs_table_count(table));
+            rows.push(count);
+            assert!(columns[2].ptr.is_null());
+            assert_eq!(columns[2].stride, 0);
+            for row in 0..count as usize {
+                let position = columns[0].ptr.add(row * columns[0].stride) as *mut f32;
+                let velocity = columns[1].ptr.add(row * columns[1].stride) as *const f32;
+                *position += *velocity;
+            }
+            flecs_query_populate(iter, false);
+        }
+        flecs_iter_destroy(iter);
+        rows.sort();
+        assert_eq!(rows, vec![2, 3]);
+        for (index, entity) in entities.iter().enumerate() {
+            assert_eq!(position_x(*entity, position), index as f32);
+        }
+        assert!(changed(reader));
+
+        let iter = flecs_query_iter(query);
+        while flecs_query_next_table(iter) {
+            let table = flecs_iter_table(iter);
+            let column = flecs_table_get_column(table, flecs_table_get_column_index(table, position), 0) as *mut f32;
+            *column += 1.0;
+        }
+        flecs_iter_destroy(iter);
+        assert!(!changed(reader));
+    }
+}
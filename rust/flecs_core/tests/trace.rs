@@ -0,0 +1,110 @@
+// Chrome trace recordings of frames and system runs
+
+mod common;
+
+use common::*;
+use flecs_core::ecs::*;
+use flecs_core::*;
+use std::ffi::CString;
+
+#[derive(Clone, Copy, Default)]
+struct Ticks {
+    count: u32,
+}
+
+impl Component for Ticks {
+    const NAME: &'static str = concat!(module_path!(), "::Ticks");
+}
+
+#[test]
+fn trace_records_frames_and_systems() {
+    let (_lock, mut world) = world();
+    let entity = world.entity();
+    world.set(entity, Ticks::default());
+    let system = world.system::<(&mut Ticks,), _>(|_, (ticks,)| ticks.count += 1);
+    world.set_name(system.entity(), "TraceTick");
+
+    let path = std::env::temp_dir().join(format!("flecs_core_trace_{}.json", std::process::id()));
+    let path_str = CString::new(path.to_str().unwrap()).unwrap();
+    unsafe {
+        assert!(flecs_trace_start(2, path_str.as_ptr()));
+        assert!(!flecs_trace_start(2, path_str.as_ptr()));
+    }
+    world.progress(0.0);
+    world.progress(0.0);
+    // The recording wrote its file and stopped after two frames
+    world.progress(0.0);
+    assert!(unsafe { !flecs_trace_stop() });
+    assert_eq!(world.get::<Ticks>(entity).unwrap().count, 3);
+
+    let json = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(json.starts_with("{\"traceEvents\":["), "{}", json);
+    assert_eq!(json.matches("{\"name\":\"frame\",\"cat\":\"frame\",\"ph\":\"X\"").count(), 2, "{}", json);
+    assert_eq!(json.matches("{\"name\":\"TraceTick\",\"cat\":\"OnUpdate\"").count(), 2, "{}", json);
+    assert_eq!(json.matches("{\"name\":\"OnUpdate\",\"cat\":\"phase\"").count(), 2, "{}", json);
+    system.delete(&mut world);
+}
+
+#[test]
+fn trace_categories_are_pipeline_phases() {
+    let (_lock, mut world) = world();
+    let entity = world.entity();
+    world.set(entity, Ticks::default());
+    let update = world.system::<(&mut Ticks,), _>(|_, (ticks,)| ticks.count += 1);
+    world.set_name(update.entity(), "TraceUpdate");
+    let late = world.system::<(&mut Ticks,), _>(|_, (ticks,)| ticks.count += 10);
+    world.set_name(late.entity(), "TraceLate");
+    // The late system depends on an entity that isn't a phase itself, and
+    // runs in the phase that one depends on
+    let group = world.entity();
+    world.set_name(group, "TraceLateGroup");
+    unsafe {
+        let late = late.entity().id();
+        ecs_add_id(*WORLD, group.id(), ecs_make_pair(EcsDependsOn, EcsPostUpdate));
+        ecs_remove_id(*WORLD, late, ecs_make_pair(EcsDependsOn, EcsOnUpdate));
+        ecs_add_id(*WORLD, late, ecs_make_pair(EcsDependsOn, group.id()));
+    }
+
+    let path = std::env::temp_dir().join(format!("flecs_core_trace_phases_{}.json", std::process::id()));
+    let path_str = CString::new(path.to_str().unwrap()).unwrap();
+    assert!(unsafe { flecs_trace_start(1, path_str.as_ptr()) });
+    world.progress(0.0);
+    assert_eq!(world.get::<Ticks>(entity).unwrap().count, 11);
+
+    let json = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(json.matches("{\"name\":\"TraceUpdate\",\"cat\":\"OnUpdate\"").count(), 1, "{}", json);
+    assert_eq!(json.matches("{\"name\":\"TraceLate\",\"cat\":\"PostUpdate\"").count(), 1, "{}", json);
+    assert_eq!(json.matches("{\"name\":\"OnUpdate\",\"cat\":\"phase\"").count(), 1, "{}", json);
+    assert_eq!(json.matches("{\"name\":\"PostUpdate\",\"cat\":\"phase\"").count(), 1, "{}", json);
+    assert!(!json.contains("TraceLateGroup"), "{}", json);
+    update.delete(&mut world);
+    late.delete(&mut world);
+    world.delete(group);
+}
+
+#[test]
+fn trace_write_failures_are_errors() {
+    let (_lock, mut world) = world();
+    let path = std::env::temp_dir().join("flecs_core_trace_missing").join("trace.json");
+    let path_str = CString::new(path.to_str().unwrap()).unwrap();
+    unsafe {
+        flecs_clear_error();
+        assert!(flecs_trace_start(1, path_str.as_ptr()));
+    }
+    world.progress(0.0);
+    unsafe {
+        assert_eq!(flecs_last_error(), ECS_OPERATION_FAILED as i32);
+        let message = std::ffi::CStr::from_ptr(flecs_last_error_message()).to_str().unwrap();
+        assert!(message.starts_with("failed to write trace to"), "{}", message);
+
+        flecs_clear_error();
+        assert!(flecs_trace_start(5, path_str.as_ptr()));
+        assert!(!flecs_trace_stop());
+        assert_eq!(flecs_last_error(), ECS_OPERATION_FAILED as i32);
+
+        assert!(!flecs_trace_start(1, std::ptr::null()));
+        assert_eq!(flecs_last_error(), ECS_INVALID_PARAMETER as i32);
+    }
+}
@@ -0,0 +1,149 @@
+// #[derive(Component)] for flecs_core::ecs. Structs are registered with their
+// members so flecs can reflect them (JSON, REST, the explorer), enums with
+// their constants, and unit structs as tags.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields};
+
+#[proc_macro_derive(Component)]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let expanded = match &input.data {
+        Data::Struct(_) => derive_struct(&input),
+        Data::Enum(_) => derive_enum(&input),
+        Data::Union(_) => Err(Error::new_spanned(&input.ident, "unions can't be components")),
+    };
+    expanded.unwrap_or_else(Error::into_compile_error).into()
+}
+
+// The repr(...) arguments of the type, e.g. ["C"] or ["i32"]
+fn reprs(input: &DeriveInput) -> syn::Result<Vec<String>> {
+    let mut reprs = Vec::new();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                reprs.push(ident.to_string());
+            }
+            Ok(())
+        })?;
+    }
+    Ok(reprs)
+}
+
+// Component::NAME, the path of the type in the crate that derives it
+fn component_path(name: &syn::Ident) -> TokenStream2 {
+    let name = name.to_string();
+    quote! { ::core::concat!(::core::module_path!(), "::", #name) }
+}
+
+fn derive_struct(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let path = component_path(name);
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "generic components can't be registered"));
+    }
+    let Data::Struct(data) = &input.data else { unreachable!() };
+
+    let fields = match &data.fields {
+        // Unit structs are tags, which the default registration handles
+        Fields::Unit => {
+            return Ok(quote! {
+                impl ::flecs_core::ecs::Component for #name {
+                    const NAME: &'static str = #path;
+                }
+            });
+        }
+        Fields::Unnamed(fields) => {
+            return Err(Error::new_spanned(fields, "components need named fields to be reflected"));
+        }
+        Fields::Named(fields) => &fields.named,
+    };
+
+    // flecs lays out members with C rules, Rust only guarantees that for repr(C)
+    if !reprs(input)?.iter().any(|repr| repr == "C") {
+        return Err(Error::new_spanned(name, "components with members must be #[repr(C)]"));
+    }
+    if fields.len() > 32 {
+        return Err(Error::new_spanned(fields, "components can have at most 32 members"));
+    }
+
+    let members = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let member_name = ident.to_string();
+        let ty = &field.ty;
+        quote! {
+            ::flecs_core::ecs::MemberDesc {
+                name: #member_name,
+                type_: <#ty as ::flecs_core::ecs::Member>::member_type(),
+                count: <#ty as ::flecs_core::ecs::Member>::COUNT,
+                offset: ::core::mem::offset_of!(#name, #ident),
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl ::flecs_core::ecs::Component for #name {
+            const NAME: &'static str = #path;
+
+            fn register() -> ::flecs_core::ecs_entity_t {
+                ::flecs_core::ecs::register_struct::<Self>(&[#(#members),*])
+            }
+        }
+
+        impl ::flecs_core::ecs::Member for #name {
+            fn member_type() -> ::flecs_core::ecs_entity_t {
+                ::flecs_core::ecs::component_id::<Self>()
+            }
+        }
+    })
+}
+
+fn derive_enum(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let path = component_path(name);
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "generic components can't be registered"));
+    }
+    let Data::Enum(data) = &input.data else { unreachable!() };
+
+    // flecs stores enums as i32
+    if !reprs(input)?.iter().any(|repr| repr == "i32" || repr == "C") {
+        return Err(Error::new_spanned(name, "enum components must be #[repr(i32)] or #[repr(C)]"));
+    }
+    if data.variants.len() > 32 {
+        return Err(Error::new_spanned(name, "enum components can have at most 32 constants"));
+    }
+
+    let mut constants = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(variant, "enum components can only have unit variants"));
+        }
+        let ident = &variant.ident;
+        let constant_name = ident.to_string();
+        constants.push(quote! { (#constant_name, #name::#ident as i32) });
+    }
+
+    Ok(quote! {
+        const _: () = assert!(
+            ::core::mem::size_of::<#name>() == ::core::mem::size_of::<i32>(),
+            "enum components must have the size of an i32",
+        );
+
+        impl ::flecs_core::ecs::Component for #name {
+            const NAME: &'static str = #path;
+
+            fn register() -> ::flecs_core::ecs_entity_t {
+                ::flecs_core::ecs::register_enum::<Self>(&[#(#constants),*])
+            }
+        }
+
+        impl ::flecs_core::ecs::Member for #name {
+            fn member_type() -> ::flecs_core::ecs_entity_t {
+                ::flecs_core::ecs::component_id::<Self>()
+            }
+        }
+    })
+}
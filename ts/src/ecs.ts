@@ -1,5 +1,4 @@
 import { flecs_core, Pointer } from './emscripten'
-import SparseMap from './utils/sparse-map'
 import { v4 as uuidv4 } from 'uuid'
 
 export enum Type {
@@ -18,6 +17,19 @@ export enum Type {
     Array,
     U32Array,
     F32Array,
+    // Growable vectors owned by the component, in the order of their element types
+    U8Vector,
+    U16Vector,
+    U32Vector,
+    U64Vector,
+    I8Vector,
+    I16Vector,
+    I32Vector,
+    I64Vector,
+    F32Vector,
+    F64Vector,
+    BoolVector,
+    StringVector,
 }
 export type JsPrimitive = string | number | boolean 
 export type Types = { [key: string]: Type }
@@ -33,8 +45,10 @@ export type ComponentValues<I> = { [K in keyof I]: I[K] | JsPrimitive }
 export type FlecsComponent<I, T> = {
     [key in keyof ComponentValues<I>]: ComponentValues<I>[keyof ComponentValues<I>] | Types[keyof Types]
 } 
-export type EntityID = number
-export type ComponentID = number
+// Ids are 64-bit and carry generation and pair bits in the upper half,
+// so they cross the wasm boundary as BigInts (requires -sWASM_BIGINT)
+export type EntityID = bigint
+export type ComponentID = bigint
 export type ComponentName = string
 export type TagName = string
 
@@ -51,7 +65,35 @@ export const TypeSizes = {
     [Type.F64]: 8,
     [Type.Bool]: 1,
     [Type.String]: 4,
-    [Type.Array]: 4
+    [Type.Array]: 4,
+    // Vectors are an ecs_vec_t: a pointer to the elements, count and capacity
+    [Type.U8Vector]: 12,
+    [Type.U16Vector]: 12,
+    [Type.U32Vector]: 12,
+    [Type.U64Vector]: 12,
+    [Type.I8Vector]: 12,
+    [Type.I16Vector]: 12,
+    [Type.I32Vector]: 12,
+    [Type.I64Vector]: 12,
+    [Type.F32Vector]: 12,
+    [Type.F64Vector]: 12,
+    [Type.BoolVector]: 12,
+    [Type.StringVector]: 12
+}
+
+// Typed arrays for the elements of numeric vectors
+export const VectorArrays = {
+    [Type.U8Vector]: Uint8Array,
+    [Type.U16Vector]: Uint16Array,
+    [Type.U32Vector]: Uint32Array,
+    [Type.U64Vector]: BigUint64Array,
+    [Type.I8Vector]: Int8Array,
+    [Type.I16Vector]: Int16Array,
+    [Type.I32Vector]: Int32Array,
+    [Type.I64Vector]: BigInt64Array,
+    [Type.F32Vector]: Float32Array,
+    [Type.F64Vector]: Float64Array,
+    [Type.BoolVector]: Uint8Array
 }
 
 export const checkType = (value: JsPrimitive): Type => {
@@ -68,17 +110,17 @@ export const checkType = (value: JsPrimitive): Type => {
 }
 
 // ComponentID -> ComponentType
-const ComponentsTypeCache = new SparseMap<ComponentType>(10_000)
+const ComponentsTypeCache = new Map<ComponentID, ComponentType>()
 // ComponentName -> ComponentID
 const ComponentIDCache = new Map<ComponentName, ComponentID>()
 
 export class ComponentType {
-    public id: EntityID = 0
+    public id: EntityID = 0n
     public types: Types = {}
 }
 
 export class Component {
-    public id: EntityID = 0
+    public id: EntityID = 0n
     public ptr: Pointer = 0
     public typesInfo: TypesInfo = {}
 
@@ -87,11 +129,11 @@ export class Component {
 }
 
 export class Tag {
-    public id: EntityID = 0
+    public id: EntityID = 0n
 }
 
 export class Entity {
-    public id: EntityID = 0
+    public id: EntityID = 0n
 
     constructor(name?: string, bulkCreated: boolean = false) {
         if(bulkCreated) return
@@ -145,12 +187,11 @@ export class Entity {
         const count = flecs_core._flecs_iter_count(iterPtr)
         const childrenPtr = flecs_core._flecs_child_entities(iterPtr)
         const entities = new Array<Entity>()
-        // Iterate over HEAPU32 and get the children
-        const ptrIndex = childrenPtr / 4
+        // Iterate over HEAPU64 and get the children
+        const ptrIndex = childrenPtr / 8
         for (let i = 0; i < count; i++) {
-            const child = flecs_core.HEAPU32[ptrIndex + (i * 2)]
-            const entity = new Entity()
-            entity.id = child
+            const entity = new Entity(null, true)
+            entity.id = flecs_core.HEAPU64[ptrIndex + i]
             entities.push(entity)
         }
 
@@ -169,12 +210,12 @@ export class Entity {
 
     static bulkCreate(count: number) {
         const entitiesPtr = flecs_core._flecs_entity_create_bulk(count)
-        const ptrIndex = entitiesPtr / 4
+        const ptrIndex = entitiesPtr / 8
         
         const entities = new Array<Entity>()
         for (let i = 0; i < count; i++) {
             const entity = new Entity(null, true)
-            entity.id = flecs_core.HEAPU32[ptrIndex + (i * 2)]
+            entity.id = flecs_core.HEAPU64[ptrIndex + i]
             entities.push(entity)
         }
 
@@ -182,7 +223,7 @@ export class Entity {
     }
 
     static bulkCreateComponents(entityCount: number, ...components: (typeof Component)[]) {
-        const componentIds = new Uint32Array(components.length)
+        const componentIds = new BigUint64Array(components.length)
         // Iterate and get index and component 
         for (let i = 0; i < components.length; i++) {
             const component = components[i]
@@ -197,15 +238,15 @@ export class Entity {
         const componentsBuffer = flecs_core._malloc(componentIds.length * componentIds.BYTES_PER_ELEMENT)
         
         // Write array of pointers to memory
-        flecs_core.HEAPU32.set(componentIds, componentsBuffer / componentIds.BYTES_PER_ELEMENT)
+        flecs_core.HEAPU64.set(componentIds, componentsBuffer / componentIds.BYTES_PER_ELEMENT)
 
         const entitiesPtr = flecs_core._flecs_entity_create_bulk_components(entityCount, componentIds.length, componentsBuffer)
-        const ptrIndex = entitiesPtr / 4
+        const ptrIndex = entitiesPtr / 8
         
         const entities = new Array<Entity>()
         for (let i = 0; i < entityCount; i++) {
             const entity = new Entity(null, true)
-            entity.id = flecs_core.HEAPU32[ptrIndex + (i * 2)]
+            entity.id = flecs_core.HEAPU64[ptrIndex + i]
             entities.push(entity)
         }
 
@@ -213,7 +254,7 @@ export class Entity {
     }
 
     static bulkCreateComponentsData(entityCount: number, ...components: (typeof Component)[]) {
-        const componentIds = new Uint32Array(components.length)
+        const componentIds = new BigUint64Array(components.length)
         // Iterate and get index and component 
         for (let i = 0; i < components.length; i++) {
             const component = components[i]
@@ -228,15 +269,15 @@ export class Entity {
         const componentsBuffer = flecs_core._malloc(componentIds.length * componentIds.BYTES_PER_ELEMENT)
         
         // Write array of pointers to memory
-        flecs_core.HEAPU32.set(componentIds, componentsBuffer / componentIds.BYTES_PER_ELEMENT)
+        flecs_core.HEAPU64.set(componentIds, componentsBuffer / componentIds.BYTES_PER_ELEMENT)
 
         const entitiesPtr = flecs_core._flecs_entity_create_bulk_components(entityCount, componentIds.length, componentsBuffer)
-        const ptrIndex = entitiesPtr / 4
+        const ptrIndex = entitiesPtr / 8
         
         const entities = new Array<Entity>()
         for (let i = 0; i < entityCount; i++) {
             const entity = new Entity(null, true)
-            entity.id = flecs_core.HEAPU32[ptrIndex + (i * 2)]
+            entity.id = flecs_core.HEAPU64[ptrIndex + i]
             entities.push(entity)
         }
 
@@ -291,7 +332,7 @@ export class World {
                                 }
                                 case Type.U32Array:
                                 {
-                                    const arrayPtr = flecs_core._flecs_component_get_member_f32array(component.ptr, typesInfo.offset)
+                                    const arrayPtr = flecs_core._flecs_component_get_member_u32array(component.ptr, typesInfo.offset)
                                     let length = new Uint32Array(flecs_core.HEAPU32.buffer, arrayPtr, 1)[0]
 
                                     // TODO: Memory in the heap is a floating point value
@@ -301,7 +342,7 @@ export class World {
                                         length = 0
 
                                     // Get array from emscripten heap
-                                    return new Uint32Array(flecs_core.HEAPF32.buffer, arrayPtr + TypeSizes[Type.F32], length)
+                                    return new Uint32Array(flecs_core.HEAPU32.buffer, arrayPtr + TypeSizes[Type.U32], length)
                                 }
                                 case Type.F32Array:
                                 {
@@ -317,6 +358,27 @@ export class World {
                                     // Get array from emscripten heap
                                     return new Float32Array(flecs_core.HEAPF32.buffer, arrayPtr + TypeSizes[Type.F32], length)
                                 }
+                                case Type.StringVector:
+                                {
+                                    const countPtr = flecs_core._malloc(TypeSizes[Type.I32])
+                                    const stringsPtr = flecs_core._flecs_component_get_member_vector(component.ptr, typesInfo.offset, countPtr)
+                                    const count = flecs_core.HEAP32[countPtr / TypeSizes[Type.I32]]
+                                    flecs_core._free(countPtr)
+                                    return Array.from(new Uint32Array(flecs_core.HEAPU32.buffer, stringsPtr, count), (stringPtr) => flecs_core.UTF8ToString(stringPtr))
+                                }
+                                default:
+                                {
+                                    const VectorArray = VectorArrays[typesInfo.type]
+                                    if(!VectorArray)
+                                        break
+                                    const countPtr = flecs_core._malloc(TypeSizes[Type.I32])
+                                    const elementsPtr = flecs_core._flecs_component_get_member_vector(component.ptr, typesInfo.offset, countPtr)
+                                    const count = flecs_core.HEAP32[countPtr / TypeSizes[Type.I32]]
+                                    flecs_core._free(countPtr)
+                                    // Copied, because the elements move when the vector grows
+                                    const elements = new VectorArray(flecs_core.HEAPU8.buffer, elementsPtr, count).slice()
+                                    return typesInfo.type === Type.BoolVector ? Array.from(elements, (element) => element !== 0) : elements
+                                }
                             }
                         }
                         return value
@@ -362,9 +424,10 @@ export class World {
                                 case Type.String:
                                 {
                                     const stringPtr = flecs_core.allocateUTF8(value)
-                                    // TODO: Free memory
-                                    flecs_core._flecs_component_set_member_string(component.ptr, typesInfo.offset, stringPtr)
-                                   break
+                                    // The component keeps a copy
+                                    flecs_core._flecs_component_set_member_string(component.id, component.ptr, typesInfo.offset, stringPtr)
+                                    flecs_core._free(stringPtr)
+                                    break
                                 }
                                 case Type.U32Array:
                                 {
@@ -377,12 +440,12 @@ export class World {
 
                                     // Allocate memory for the array
                                     const arrayBuffer = flecs_core._malloc((valueWithLength as Uint32Array).length * (valueWithLength as Uint32Array).BYTES_PER_ELEMENT)
-                                    // TODO: Free memory
                                     // Write array of string pointers to memory
-                                    flecs_core.HEAPF32.set((valueWithLength as Uint32Array), arrayBuffer / (valueWithLength as Uint32Array).BYTES_PER_ELEMENT)
+                                    flecs_core.HEAPU32.set((valueWithLength as Uint32Array), arrayBuffer / (valueWithLength as Uint32Array).BYTES_PER_ELEMENT)
 
-                                    // Set component member
-                                    flecs_core._flecs_component_set_member_f32array(component.ptr, typesInfo.offset, arrayBuffer)
+                                    // Set component member, which keeps a copy
+                                    flecs_core._flecs_component_set_member_u32array(component.id, component.ptr, typesInfo.offset, arrayBuffer)
+                                    flecs_core._free(arrayBuffer)
                                     break
                                 }
                                 case Type.F32Array:
@@ -396,16 +459,42 @@ export class World {
 
                                     // Allocate memory for the array
                                     const arrayBuffer = flecs_core._malloc((valueWithLength as Float32Array).length * (valueWithLength as Float32Array).BYTES_PER_ELEMENT)
-                                    // TODO: Free memory
                                     // Write array of string pointers to memory
                                     flecs_core.HEAPF32.set((valueWithLength as Float32Array), arrayBuffer / (valueWithLength as Float32Array).BYTES_PER_ELEMENT)
 
-                                    // Set component member
-                                    flecs_core._flecs_component_set_member_f32array(component.ptr, typesInfo.offset, arrayBuffer)
-                                   break
+                                    // Set component member, which keeps a copy
+                                    flecs_core._flecs_component_set_member_f32array(component.id, component.ptr, typesInfo.offset, arrayBuffer)
+                                    flecs_core._free(arrayBuffer)
+                                    break
+                                }
+                                case Type.StringVector:
+                                {
+                                    const stringPtrs = Uint32Array.from(value as string[], (string) => flecs_core.allocateUTF8(string))
+                                    const stringsPtr = flecs_core._malloc(stringPtrs.byteLength)
+                                    flecs_core.HEAPU32.set(stringPtrs, stringsPtr / stringPtrs.BYTES_PER_ELEMENT)
+
+                                    // The component keeps copies of the strings
+                                    flecs_core._flecs_component_set_member_string_vector(component.id, component.ptr, typesInfo.offset, stringsPtr, stringPtrs.length)
+                                    stringPtrs.forEach((stringPtr) => flecs_core._free(stringPtr))
+                                    flecs_core._free(stringsPtr)
+                                    break
                                 }
                                 default:
+                                {
+                                    const VectorArray = VectorArrays[typesInfo.type]
+                                    if(!VectorArray)
+                                        break
+                                    const elements = typesInfo.type === Type.BoolVector
+                                        ? Uint8Array.from(value as boolean[], (element) => element ? 1 : 0)
+                                        : new VectorArray(value)
+                                    const elementsPtr = flecs_core._malloc(Math.max(elements.byteLength, 1))
+                                    flecs_core.HEAPU8.set(new Uint8Array(elements.buffer, elements.byteOffset, elements.byteLength), elementsPtr)
+
+                                    // The component keeps a copy
+                                    flecs_core._flecs_component_set_member_vector(component.id, component.ptr, typesInfo.offset, elementsPtr, elements.length, elements.BYTES_PER_ELEMENT)
+                                    flecs_core._free(elementsPtr)
                                     break
+                                }
                             }
                         }
                     }
@@ -496,7 +585,7 @@ export class World {
     }
     
     static query(...components: (typeof Component)[]): Query {
-        const componentIds = new Array<number>()
+        const componentIds = new Array<ComponentID>()
             const indexes = new Array<ComponentName>()
 
             for (const component of components) {
@@ -508,11 +597,11 @@ export class World {
                 indexes.push(component.name)
             }
 
-            const BYTES_PER_ELEMENT = 4
+            const BYTES_PER_ELEMENT = 8
             // Allocate array of component ids
-            const buffer = flecs_core._malloc(componentIds.length)
+            const buffer = flecs_core._malloc(componentIds.length * BYTES_PER_ELEMENT)
             // Write array of component ids to memory
-            flecs_core.HEAPU32.set(componentIds, buffer / BYTES_PER_ELEMENT)
+            flecs_core.HEAPU64.set(componentIds, buffer / BYTES_PER_ELEMENT)
 
             // Create query
             const query = new Query(flecs_core._flecs_query_create(buffer, componentIds.length), indexes)
@@ -7,11 +7,11 @@ export type Pointer = number
 export type u8 = number
 export type u16 = number
 export type u32 = number
-export type u64 = number
+export type u64 = bigint
 export type i8 = number
 export type i16 = number
 export type i32 = number
-export type i64 = number
+export type i64 = bigint
 export type f32 = number
 export type f64 = number
 export type Bool = number
@@ -23,16 +23,22 @@ export interface EmscriptenModuleExtended extends EmscriptenModule {
     writeArrayToMemory: typeof writeArrayToMemory,
     UTF8ToString: typeof UTF8ToString,
     _m_free: (ptr: Pointer) => void,
+    HEAP64: BigInt64Array,
+    HEAPU64: BigUint64Array,
 }
 
 export interface CoreAPI {
-    _flecs_component_create: (name: Pointer, member_names: Pointer, member_names_count: u32, member_values: Pointer, member_values_count: u32) => Pointer,
-    _flecs_tag_create: (name: Pointer) => Pointer,
+    _flecs_component_create: (name: Pointer, member_names: Pointer, member_names_count: u32, member_values: Pointer, member_values_count: u32) => EntityID,
+    _flecs_tag_create: (name: Pointer) => EntityID,
     _flecs_entity_create: () => EntityID,
     _flecs_entity_create_named: (name: Pointer) => EntityID,
     _flecs_entity_create_bulk: (count: u32) => Pointer,
     _flecs_entity_create_bulk_components: (entity_count: u32, component_count: u32, componenets: Pointer) => Pointer,
     _flecs_entity_get_component: (entity: EntityID, component: EntityID) => Pointer,
+    _flecs_ref_create: (entity: EntityID, component: EntityID) => Pointer,
+    _flecs_ref_get: (component_ref: Pointer) => Pointer,
+    _flecs_ref_update: (component_ref: Pointer) => void,
+    _flecs_ref_destroy: (component_ref: Pointer) => void,
     _flecs_entity_add_component: (entity: EntityID, component: EntityID),
     _flecs_entity_remove_component: (entity: EntityID, component: EntityID) => void,
     _flecs_entity_add_tag: (entity: EntityID, tag: EntityID) => Pointer,
@@ -46,8 +52,11 @@ export interface CoreAPI {
     _flecs_iter_ptrs: (iter: Pointer, component_query_index: u32) => Pointer,
     _flecs_query_iter_component: (component_array_ptr: Pointer, component_index: u32, count: i32, component_id: EntityID) => Pointer,
     _flecs_query_field: (iter: Pointer, term_index: u32, count: u32, index: u32) => Pointer,
-    _flecs_query_entity: (iter: Pointer, count: u32, index: u32) => Pointer,
+    _flecs_query_entity: (iter: Pointer, count: u32, index: u32) => EntityID,
     _flecs_iter_count: (iter: Pointer) => i32,
+    _flecs_system_create_indexed: (callback_index: u32, ctx: Pointer) => Pointer,
+    _flecs_observer_create_indexed: (callback_index: u32, ctx: Pointer) => Pointer,
+    _flecs_set_callback_dispatcher: (dispatcher: Pointer) => void,
     _flecs_component_set_member_u8: (component_ptr: Pointer, offset: u32, value: u8) => void,
     _flecs_component_get_member_u8: (component_ptr: Pointer, offset: u32) => u8,
     _flecs_component_set_member_u16: (component_ptr: Pointer, offset: u32, value: u16) => void,
@@ -55,7 +64,7 @@ export interface CoreAPI {
     _flecs_component_set_member_u32: (component_ptr: Pointer, offset: u32, value: u32) => void,
     _flecs_component_get_member_u32: (component_ptr: Pointer, offset: u32) => u8,
     _flecs_component_set_member_u64: (component_ptr: Pointer, offset: u32, value: u64) => void,
-    _flecs_component_get_member_u64: (component_ptr: Pointer, offset: u32) => u8,
+    _flecs_component_get_member_u64: (component_ptr: Pointer, offset: u32) => u64,
     _flecs_component_set_member_i8: (component_ptr: Pointer, offset: u32, value: i8) => void,
     _flecs_component_get_member_i8: (component_ptr: Pointer, offset: u32) => i8,
     _flecs_component_set_member_i16: (component_ptr: Pointer, offset: u32, value: i16) => void,
@@ -63,17 +72,63 @@ export interface CoreAPI {
     _flecs_component_set_member_i32: (component_ptr: Pointer, offset: u32, value: i32) => void,
     _flecs_component_get_member_i32: (component_ptr: Pointer, offset: u32) => i8,
     _flecs_component_set_member_i64: (component_ptr: Pointer, offset: u32, value: i64) => void,
-    _flecs_component_get_member_i64: (component_ptr: Pointer, offset: u32) => i8,
+    _flecs_component_get_member_i64: (component_ptr: Pointer, offset: u32) => i64,
     _flecs_component_set_member_f32: (component_ptr: Pointer, offset: u32, value: f32) => void,
     _flecs_component_get_member_f32: (component_ptr: Pointer, offset: u32) => f32,
     _flecs_component_set_member_f64: (component_ptr: Pointer, offset: u32, value: f64) => void,
     _flecs_component_get_member_f64: (component_ptr: Pointer, offset: u32) => f64,
     _flecs_component_set_member_bool: (component_ptr: Pointer, offset: u32, value: Bool) => void,
     _flecs_component_get_member_bool: (component_ptr: Pointer, offset: u32) => Bool,
-    _flecs_component_set_member_string: (component_ptr: Pointer, offset: u32, value: u32) => f64,
+    _flecs_component_set_member_string: (component: EntityID, component_ptr: Pointer, offset: u32, value: Pointer) => boolean,
     _flecs_component_get_member_string: (component_ptr: Pointer, offset: u32) => Pointer,
-    _flecs_component_set_member_f32array: (component_ptr: Pointer, offset: u32, value: u32) => f64,
+    _flecs_component_set_member_u32array: (component: EntityID, component_ptr: Pointer, offset: u32, value: Pointer) => boolean,
+    _flecs_component_get_member_u32array: (component_ptr: Pointer, offset: u32) => Pointer,
+    _flecs_component_set_member_f32array: (component: EntityID, component_ptr: Pointer, offset: u32, value: Pointer) => boolean,
     _flecs_component_get_member_f32array: (component_ptr: Pointer, offset: u32) => Pointer,
+    _flecs_component_set_member_vector: (component: EntityID, component_ptr: Pointer, offset: u32, values: Pointer, count: i32, element_size: i32) => boolean,
+    _flecs_component_set_member_string_vector: (component: EntityID, component_ptr: Pointer, offset: u32, values: Pointer, count: i32) => boolean,
+    _flecs_component_get_member_vector: (component_ptr: Pointer, offset: u32, count: Pointer) => Pointer,
+    _flecs_stats_create: () => Pointer,
+    _flecs_stats_update: (stats: Pointer) => void,
+    _flecs_stats_world: (stats: Pointer, world_stats: Pointer) => void,
+    _flecs_stats_systems: (stats: Pointer, count: Pointer) => Pointer,
+    _flecs_stats_to_json: (stats: Pointer) => Pointer,
+    _flecs_stats_destroy: (stats: Pointer) => void,
+    _flecs_trace_start: (frame_count: i32, path: Pointer) => boolean,
+    _flecs_trace_stop: () => boolean,
+    _flecs_metric_create_member: (name: Pointer, component: EntityID, member: Pointer, kind: u8) => EntityID,
+    _flecs_metric_create_id: (name: Pointer, id: EntityID, kind: u8, targets: boolean) => EntityID,
+    _flecs_metric_value: (metric: EntityID, value: Pointer) => boolean,
+    _flecs_metric_instances: (metric: EntityID, count: Pointer) => Pointer,
+    _flecs_alert_create: (name: Pointer, expr: Pointer, message: Pointer, severity: u8, retain_period: f32) => EntityID,
+    _flecs_entity_alert_count: (entity: EntityID, alert: EntityID) => i32,
+    _flecs_alerts_active: (alert: EntityID, count: Pointer) => Pointer,
+    _flecs_rest_disable: () => void,
+    _flecs_rest_request: (method: Pointer, path: Pointer, code: Pointer) => Pointer,
+    _flecs_http_route_indexed: (method: u8, path: Pointer, callback_index: u32, ctx: Pointer) => void,
+    _flecs_http_route_remove: (method: u8, path: Pointer) => void,
+    _flecs_set_http_dispatcher: (dispatcher: Pointer) => void,
+    _flecs_http_disable: () => void,
+    _flecs_http_request: (method: Pointer, path: Pointer, code: Pointer) => Pointer,
+    _flecs_http_request_method: (request: Pointer) => u8,
+    _flecs_http_request_path: (request: Pointer) => Pointer,
+    _flecs_http_request_body: (request: Pointer) => Pointer,
+    _flecs_http_request_param: (request: Pointer, name: Pointer) => Pointer,
+    _flecs_http_request_header: (request: Pointer, name: Pointer) => Pointer,
+    _flecs_http_reply_set: (reply: Pointer, code: i32, body: Pointer) => void,
+    _flecs_log_set_level: (level: i32) => i32,
+    _flecs_log_get_level: () => i32,
+    _flecs_log_enable_colors: (enabled: boolean) => boolean,
+    _flecs_log_enable_timestamp: (enabled: boolean) => boolean,
+    _flecs_log_set_callback_indexed: (callback_index: u32, ctx: Pointer) => void,
+    _flecs_set_log_dispatcher: (dispatcher: Pointer) => void,
+    _flecs_set_abort_hook: (hook: Pointer) => void,
+    _flecs_last_error: () => i32,
+    _flecs_last_error_message: () => Pointer,
+    _flecs_clear_error: () => void,
+    _flecs_set_allocator: (allocator: Pointer) => boolean,
+    _flecs_set_memory_tracking: (enabled: boolean) => boolean,
+    _flecs_memory_stats: (stats: Pointer) => boolean,
 }
 
 export const flecs_core: EmscriptenModuleExtended & CoreAPI = window['flecs_core']